@@ -0,0 +1,183 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+/// Size variant of an artwork or avatar image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageSize {
+    /// 500x500 pixels.
+    T500x500,
+    /// 400x400 pixels.
+    Crop,
+    /// 300x300 pixels.
+    T300x300,
+    /// 100x100 pixels, the size the API links to.
+    Large,
+    /// 67x67 pixels, only for artwork.
+    T67x67,
+    /// 47x47 pixels.
+    Badge,
+    /// 32x32 pixels.
+    Small,
+    /// 20x20 pixels for artwork, 18x18 pixels for avatars.
+    Tiny,
+    /// 16x16 pixels.
+    Mini,
+    /// The image as it was uploaded.
+    Original,
+}
+
+impl ImageSize {
+    /// Returns the name of the size as it appears in image urls.
+    pub fn to_str(&self) -> &str {
+        match *self {
+            ImageSize::T500x500 => "t500x500",
+            ImageSize::Crop => "crop",
+            ImageSize::T300x300 => "t300x300",
+            ImageSize::Large => "large",
+            ImageSize::T67x67 => "t67x67",
+            ImageSize::Badge => "badge",
+            ImageSize::Small => "small",
+            ImageSize::Tiny => "tiny",
+            ImageSize::Mini => "mini",
+            ImageSize::Original => "original",
+        }
+    }
+
+    fn all() -> [ImageSize; 10] {
+        [
+            ImageSize::T500x500,
+            ImageSize::Crop,
+            ImageSize::T300x300,
+            ImageSize::Large,
+            ImageSize::T67x67,
+            ImageSize::Badge,
+            ImageSize::Small,
+            ImageSize::Tiny,
+            ImageSize::Mini,
+            ImageSize::Original,
+        ]
+    }
+}
+
+impl fmt::Display for ImageSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+/// URL to an artwork or avatar image, which is available in several sizes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct ArtworkUrl(String);
+
+impl ArtworkUrl {
+    /// Constructs an image url from the url returned by the API.
+    pub fn new(url: &str) -> ArtworkUrl {
+        ArtworkUrl(url.to_owned())
+    }
+
+    /// Returns the url as returned by the API.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the url of the image in the given `size`.
+    ///
+    /// Urls that don't end with a known size, such as default avatars, are returned unchanged.
+    pub fn size(&self, size: ImageSize) -> String {
+        let url = &self.0;
+        let path_end = url.find(|c| c == '?' || c == '#').unwrap_or(url.len());
+        let file_start = url[..path_end].rfind('/').map_or(0, |index| index + 1);
+        let file_name = &url[file_start..path_end];
+
+        let (stem, extension) = match file_name.rfind('.') {
+            Some(index) => file_name.split_at(index),
+            None => (file_name, ""),
+        };
+
+        let size_start = match stem.rfind('-') {
+            Some(index) => index + 1,
+            None => return url.clone(),
+        };
+
+        if !ImageSize::all()
+            .iter()
+            .any(|known| known.to_str() == &stem[size_start..])
+        {
+            return url.clone();
+        }
+
+        format!(
+            "{}{}{}{}",
+            &url[..file_start + size_start],
+            size.to_str(),
+            extension,
+            &url[path_end..]
+        )
+    }
+}
+
+impl fmt::Display for ArtworkUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn test_size_variants() {
+        let url = ArtworkUrl::new("https://i1.sndcdn.com/artworks-000153912187-mmuhcm-large.jpg");
+
+        assert_eq!(
+            url.size(ImageSize::T500x500),
+            "https://i1.sndcdn.com/artworks-000153912187-mmuhcm-t500x500.jpg"
+        );
+        assert_eq!(
+            url.size(ImageSize::Original),
+            "https://i1.sndcdn.com/artworks-000153912187-mmuhcm-original.jpg"
+        );
+        assert_eq!(url.size(ImageSize::Large), url.as_str());
+    }
+
+    #[test]
+    fn test_size_with_query() {
+        let url =
+            ArtworkUrl::new("https://i1.sndcdn.com/avatars-000004193858-jnf2pd-large.jpg?e76cf77");
+
+        assert_eq!(
+            url.size(ImageSize::Badge),
+            "https://i1.sndcdn.com/avatars-000004193858-jnf2pd-badge.jpg?e76cf77"
+        );
+    }
+
+    #[test]
+    fn test_unknown_size() {
+        let url = ArtworkUrl::new("https://a1.sndcdn.com/images/default_avatar_large.png");
+
+        assert_eq!(url.size(ImageSize::Crop), url.as_str());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let url: ArtworkUrl =
+            serde_json::from_str(r#""https://i1.sndcdn.com/artworks-1-a-large.jpg""#).unwrap();
+
+        assert_eq!(url.as_str(), "https://i1.sndcdn.com/artworks-1-a-large.jpg");
+        assert_eq!(
+            serde_json::to_string(&url).unwrap(),
+            r#""https://i1.sndcdn.com/artworks-1-a-large.jpg""#
+        );
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use futures::future;
+use futures::{stream, Future, Stream};
+
+use client::Client;
+use error::{Error, Result};
+#[cfg(feature = "id3")]
+use metadata::OriginalFormat;
+use track::Track;
+
+/// Filename template used if none is set.
+pub const DEFAULT_TEMPLATE: &'static str = "{user.username}/{title}.{ext}";
+
+/// Downloads many tracks concurrently to files named after a template.
+///
+/// Tracks that are downloadable are downloaded in their original format, other tracks are
+/// streamed. Audio is written to a `.part` file that is renamed once it is complete, so files
+/// that already exist are skipped and interrupted downloads are resumed.
+///
+/// # Examples
+///
+/// ```
+/// use soundcloud::{BatchDownloader, Client};
+///
+/// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+/// let report = BatchDownloader::new(&client)
+///     .directory("archive")
+///     .concurrency(8)
+///     .run(tracks)
+///     .wait()
+///     .unwrap();
+///
+/// println!("{}", report);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BatchDownloader<'a> {
+    client: &'a Client,
+    directory: PathBuf,
+    template: String,
+    concurrency: usize,
+    #[cfg(feature = "id3")]
+    tag_files: bool,
+}
+
+/// Outcome of a single track in a batch.
+#[derive(Debug)]
+pub enum BatchOutcome {
+    /// The track was downloaded, with the size of the complete file.
+    Downloaded(u64),
+    /// The file already existed.
+    Skipped,
+    /// The track couldn't be downloaded.
+    Failed(Error),
+}
+
+/// Result of a single track in a batch.
+#[derive(Debug)]
+pub struct BatchEntry {
+    /// Integer ID of the track.
+    pub track_id: u64,
+    /// Path of the output file, unless the template couldn't be expanded.
+    pub path: Option<PathBuf>,
+    /// What happened to the track.
+    pub outcome: BatchOutcome,
+}
+
+/// Summary of a batch, with an entry for every track in the order they finished.
+#[derive(Debug)]
+pub struct BatchReport {
+    /// Result of every track.
+    pub entries: Vec<BatchEntry>,
+}
+
+impl BatchReport {
+    /// Returns the number of tracks that were downloaded.
+    pub fn downloaded(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| match entry.outcome {
+                BatchOutcome::Downloaded(_) => true,
+                _ => false,
+            })
+            .count()
+    }
+
+    /// Returns the number of tracks that were skipped because their file already existed.
+    pub fn skipped(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| match entry.outcome {
+                BatchOutcome::Skipped => true,
+                _ => false,
+            })
+            .count()
+    }
+
+    /// Returns the entries of the tracks that couldn't be downloaded.
+    pub fn failures(&self) -> Vec<&BatchEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| match entry.outcome {
+                BatchOutcome::Failed(_) => true,
+                _ => false,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failures = self.failures();
+
+        write!(
+            f,
+            "{} downloaded, {} skipped, {} failed",
+            self.downloaded(),
+            self.skipped(),
+            failures.len()
+        )?;
+
+        for entry in failures {
+            if let BatchOutcome::Failed(ref error) = entry.outcome {
+                write!(f, "\ntrack {}: {}", entry.track_id, error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> BatchDownloader<'a> {
+    /// Constructs a downloader that writes to the current directory using the default template,
+    /// with up to 4 downloads at a time.
+    pub fn new(client: &'a Client) -> BatchDownloader<'a> {
+        BatchDownloader {
+            client: client,
+            directory: PathBuf::from("."),
+            template: DEFAULT_TEMPLATE.to_owned(),
+            concurrency: 4,
+            #[cfg(feature = "id3")]
+            tag_files: false,
+        }
+    }
+
+    /// Sets the directory that paths from the template are relative to.
+    pub fn directory<P: AsRef<Path>>(&mut self, directory: P) -> &mut BatchDownloader<'a> {
+        self.directory = directory.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sets the filename template.
+    ///
+    /// The placeholders `{id}`, `{title}`, `{permalink}`, `{genre}`, `{original_format}`,
+    /// `{user.id}`, `{user.username}` and `{user.permalink}` are replaced with the fields of the
+    /// track, and `{ext}` with the extension of the audio that is actually fetched, which is
+    /// `mp3` for tracks that have to be streamed. Slashes in the template separate directories,
+    /// slashes in the fields are replaced.
+    pub fn template(&mut self, template: &str) -> &mut BatchDownloader<'a> {
+        self.template = template.to_owned();
+        self
+    }
+
+    /// Sets the maximum number of tracks that are downloaded at a time.
+    pub fn concurrency(&mut self, concurrency: usize) -> &mut BatchDownloader<'a> {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets whether ID3v2 tags with the metadata and artwork of each track are written to the
+    /// files that are downloaded as MP3. Off by default.
+    #[cfg(feature = "id3")]
+    pub fn tag_files(&mut self, tag_files: bool) -> &mut BatchDownloader<'a> {
+        self.tag_files = tag_files;
+        self
+    }
+
+    /// Returns the path that `track` is downloaded to.
+    pub fn path(&self, track: &Track) -> Result<PathBuf> {
+        Ok(self.directory.join(expand_template(&self.template, track)?))
+    }
+
+    /// Downloads every track in `tracks` and returns a report once all of them have finished.
+    ///
+    /// Failed tracks don't stop the batch, they are listed in the report instead.
+    pub fn run<I>(&self, tracks: I) -> Box<Future<Item = BatchReport, Error = Error> + 'a>
+    where
+        I: IntoIterator<Item = Track>,
+        I::IntoIter: 'a,
+    {
+        let downloader = self.clone();
+
+        let report = stream::iter_ok(tracks)
+            .map(move |track| downloader.fetch(track))
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .map(|entries| BatchReport { entries: entries });
+
+        Box::new(report)
+    }
+
+    /// Downloads a single track. The returned future never fails, errors are reported in the
+    /// entry instead.
+    fn fetch(&self, track: Track) -> Box<Future<Item = BatchEntry, Error = Error> + 'a> {
+        let track_id = track.id;
+
+        let path = match self.path(&track) {
+            Ok(path) => path,
+            Err(error) => return Box::new(future::ok(failed(track_id, None, error))),
+        };
+
+        if path.exists() {
+            return Box::new(future::ok(BatchEntry {
+                track_id: track_id,
+                path: Some(path),
+                outcome: BatchOutcome::Skipped,
+            }));
+        }
+
+        if let Some(parent) = path.parent() {
+            if let Err(error) = fs::create_dir_all(parent) {
+                return Box::new(future::ok(failed(track_id, Some(path), Error::Io(error))));
+            }
+        }
+
+        let mut partial_path = OsString::from(path.as_os_str());
+        partial_path.push(".part");
+        let partial_path = PathBuf::from(partial_path);
+
+        let size = if is_downloadable(&track) {
+            self.client.download_to_file(&track, &partial_path)
+        } else {
+            self.client.stream_to_file(&track, &partial_path)
+        };
+
+        let size: Box<Future<Item = u64, Error = Error> + 'a> = Box::new(size.and_then({
+            let path = path.clone();
+
+            move |size| {
+                fs::rename(&partial_path, &path)
+                    .map(|_| size)
+                    .map_err(|error| Error::Io(error))
+            }
+        }));
+
+        #[cfg(feature = "id3")]
+        let size = self.tag(size, &path, track);
+
+        let entry = size.then(move |result| {
+            Ok(match result {
+                Ok(size) => BatchEntry {
+                    track_id: track_id,
+                    path: Some(path),
+                    outcome: BatchOutcome::Downloaded(size),
+                },
+                Err(error) => failed(track_id, Some(path), error),
+            })
+        });
+
+        Box::new(entry)
+    }
+
+    /// Tags the file at `path` once `size` resolves, if tagging is enabled and the fetched audio is
+    /// an MP3, whatever extension the template gave the file.
+    #[cfg(feature = "id3")]
+    fn tag(
+        &self,
+        size: Box<Future<Item = u64, Error = Error> + 'a>,
+        path: &Path,
+        track: Track,
+    ) -> Box<Future<Item = u64, Error = Error> + 'a> {
+        if !self.tag_files || !is_mp3(&track) {
+            return size;
+        }
+
+        let client = self.client;
+        let path = path.to_path_buf();
+
+        Box::new(size.and_then(move |size| client.tag_file(&path, &track).map(move |_| size)))
+    }
+}
+
+fn failed(track_id: u64, path: Option<PathBuf>, error: Error) -> BatchEntry {
+    BatchEntry {
+        track_id: track_id,
+        path: path,
+        outcome: BatchOutcome::Failed(error),
+    }
+}
+
+fn is_downloadable(track: &Track) -> bool {
+    track.downloadable && track.download_url.is_some()
+}
+
+/// Returns true if the audio fetched for `track` is an MP3, which streams always are.
+#[cfg(feature = "id3")]
+fn is_mp3(track: &Track) -> bool {
+    !is_downloadable(track) || track.original_format == Some(OriginalFormat::Mp3)
+}
+
+/// Replaces the `{field}` placeholders in `template` with the fields of `track`.
+fn expand_template(template: &str, track: &Track) -> Result<String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                return Err(Error::InvalidTemplate(format!(
+                    "unclosed placeholder in {}",
+                    template
+                )))
+            }
+        };
+
+        let name = &rest[start + 1..end];
+        let value = match field(track, name) {
+            Some(value) => value,
+            None => {
+                return Err(Error::InvalidTemplate(format!(
+                    "unknown placeholder {{{}}}",
+                    name
+                )))
+            }
+        };
+
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&sanitize(&value));
+        rest = &rest[end + 1..];
+    }
+
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Returns the value of the template placeholder `name` for `track`.
+fn field(track: &Track, name: &str) -> Option<String> {
+    let value = match name {
+        "id" => track.id.to_string(),
+        "title" => track.title.clone(),
+        "permalink" => track.permalink.clone(),
+        "genre" => track.genre.clone().unwrap_or_default(),
+        "original_format" => original_format(track),
+        "ext" if is_downloadable(track) => original_format(track),
+        "ext" => "mp3".to_owned(),
+        "user.id" => track.user.id.to_string(),
+        "user.username" => track.user.username.clone(),
+        "user.permalink" => track.user.permalink.clone(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Returns the original upload format of `track`, or an empty string if it isn't known.
+fn original_format(track: &Track) -> String {
+    track
+        .original_format
+        .as_ref()
+        .map_or("", |format| format.to_str())
+        .to_owned()
+}
+
+/// Replaces characters that aren't allowed in, or have special meaning in, a single path
+/// component.
+fn sanitize(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    match sanitized.trim() {
+        "" | "." | ".." => "_".to_owned(),
+        _ => sanitized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures;
+
+    fn track() -> Track {
+        let mut track = fixtures::track();
+        track.user.username = "Alex/Stevenson".to_owned();
+        track
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let track = track();
+
+        assert_eq!(
+            expand_template(DEFAULT_TEMPLATE, &track).unwrap(),
+            "Alex_Stevenson/Munching at Tiannas house.mp3"
+        );
+        assert_eq!(
+            expand_template("{title}.{original_format}", &track).unwrap(),
+            "Munching at Tiannas house.m4a"
+        );
+        assert_eq!(
+            expand_template("{user.id}/{id}-{permalink}.{ext}", &track).unwrap(),
+            "3699101/13158665-munching-at-tiannas-house.mp3"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        let track = track();
+
+        assert!(expand_template("{title", &track).is_err());
+        assert!(expand_template("{bitrate}.mp3", &track).is_err());
+    }
+
+    #[cfg(feature = "id3")]
+    #[test]
+    fn test_is_mp3() {
+        let mut track = track();
+        assert!(is_mp3(&track));
+
+        track.downloadable = true;
+        track.download_url = Some("https://api.soundcloud.com/tracks/13158665/download".to_owned());
+        assert!(!is_mp3(&track));
+
+        track.original_format = Some(OriginalFormat::Mp3);
+        assert!(is_mp3(&track));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("AC/DC: Back in Black?"), "AC_DC_ Back in Black_");
+        assert_eq!(sanitize(".."), "_");
+        assert_eq!(sanitize(""), "_");
+    }
+}
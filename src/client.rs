@@ -7,28 +7,79 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use artwork::{ArtworkUrl, ImageSize};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use date;
 use futures::future;
-use futures::future::Either;
+use futures::future::{Either, Loop};
+use futures::stream;
 use futures::{Future, Stream};
 use hyper;
 use hyper_tls;
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{self, Value};
 use tokio_core;
+use tokio_io;
+use tokio_io::AsyncWrite;
+use url::form_urlencoded;
 use url::Url;
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::path::Path;
+use std::result;
+use std::time::{Duration, Instant, SystemTime};
 
-use error::{Error, Result};
+use error::{Error, Result, StatusError};
+use hls;
+use oauth::{self, AccessToken, Grant};
+use page::{self, DEFAULT_PAGE_SIZE};
+use playlist::{Playlist, PlaylistRequestBuilder, SinglePlaylistRequestBuilder};
+use progress::Progress;
+use rate_limit::{RateLimitMetrics, RateLimiter};
+use retry::{self, RetryPolicy};
+#[cfg(feature = "id3")]
+use tag;
 use track::{SingleTrackRequestBuilder, Track, TrackRequestBuilder};
+use transcoding::{MediaLocation, Protocol, Transcoding};
+use user::{SingleUserRequestBuilder, User, UserRequestBuilder};
+use waveform::{self, Waveform};
 
 pub type Params<'a, K, V> = &'a [(K, V)];
 
 #[derive(Debug)]
 pub struct Client {
     client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: Option<String>,
+    access_token: RefCell<Option<AccessToken>>,
+    base_url: Url,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    max_redirects: usize,
+    handle: tokio_core::reactor::Handle,
     http_client: hyper::Client<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>,
 }
 
+/// Builder for a `Client` with non-default settings.
+#[derive(Debug)]
+pub struct ClientBuilder {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: Option<String>,
+    access_token: Option<AccessToken>,
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    max_redirects: usize,
+}
+
 /// Registered client application.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct App {
@@ -51,8 +102,9 @@ pub struct Comment {
     pub id: usize,
     /// API resource URL.
     pub uri: String,
-    /// Time of creation, as an unparsed string.
-    pub created_at: String,
+    /// Time of creation.
+    #[serde(with = "date")]
+    pub created_at: DateTime<Utc>,
     /// HTML comment body.
     pub body: String,
     /// Associated timestamp in milliseconds.
@@ -65,53 +117,82 @@ pub struct Comment {
     pub track_id: usize,
 }
 
-/// Registered user.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct User {
-    /// Integer ID.
-    pub id: usize,
-    /// Permalink of the resource.
-    pub permalink: String,
-    /// Username.
-    pub username: String,
-    /// API resource URL.
-    pub uri: String,
-    /// URL to the SoundCloud.com page.
-    pub permalink_url: String,
-    /// URL to a JPEG image.
-    pub avatar_url: String,
-    /// Country.
-    pub country: Option<String>,
-    /// First and last name.
-    pub full_name: Option<String>,
-    /// City.
-    pub city: Option<String>,
-    /// Description, written by the user.
-    pub description: Option<String>,
-    /// Discogs name.
-    #[serde(rename = "discogs-name")]
-    pub discogs_name: Option<String>, // discogs-name
-    /// MySpace name.
-    #[serde(rename = "myspace-name")]
-    pub myspace_name: Option<String>, // myspace-name
-    /// URL to a website.
-    pub website: Option<String>,
-    /// Custom title for the website.
-    #[serde(rename = "website-title")]
-    pub website_title: Option<String>, // website-title
-    /// Online status.
-    pub online: Option<bool>,
-    /// Number of public tracks.
-    pub track_count: Option<usize>,
-    /// Number of public playlists.
-    pub playlist_count: Option<usize>,
-    /// Number of followers.
-    pub followers_count: Option<usize>,
-    /// Number of followed users.
-    pub followings_count: Option<usize>,
-    /// Number of favorited public tracks.
-    pub public_favorites_count: Option<usize>,
-    // pub avatar_data …
+/// Resource that a SoundCloud url resolves to, determined by its `kind`.
+#[derive(Debug, Clone)]
+pub enum Resource {
+    /// Uploaded track.
+    Track(Track),
+    /// Registered user.
+    User(User),
+    /// Playlist, or set, of tracks.
+    Playlist(Playlist),
+    /// User comment.
+    Comment(Comment),
+    /// Resource of any other kind, as it was returned by the API.
+    Other(Value),
+}
+
+/// The kinds of resources that have their own `Resource` variant.
+const RESOURCE_KINDS: [&'static str; 4] = ["track", "user", "playlist", "comment"];
+
+/// Resource of a known kind, borrowed for serialization.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum KnownResourceRef<'a> {
+    Track(&'a Track),
+    User(&'a User),
+    Playlist(&'a Playlist),
+    Comment(&'a Comment),
+}
+
+/// Resource of a known kind, for deserialization.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum KnownResource {
+    Track(Track),
+    User(User),
+    Playlist(Playlist),
+    Comment(Comment),
+}
+
+impl Serialize for Resource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        let resource = match *self {
+            Resource::Track(ref track) => KnownResourceRef::Track(track),
+            Resource::User(ref user) => KnownResourceRef::User(user),
+            Resource::Playlist(ref playlist) => KnownResourceRef::Playlist(playlist),
+            Resource::Comment(ref comment) => KnownResourceRef::Comment(comment),
+            Resource::Other(ref value) => return value.serialize(serializer),
+        };
+
+        resource.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Resource {
+    fn deserialize<D>(deserializer: D) -> result::Result<Resource, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let known = match value.get("kind").and_then(Value::as_str) {
+            Some(kind) => RESOURCE_KINDS.contains(&kind),
+            None => false,
+        };
+
+        if !known {
+            return Ok(Resource::Other(value));
+        }
+
+        let resource = match KnownResource::deserialize(value).map_err(de::Error::custom)? {
+            KnownResource::Track(track) => Resource::Track(track),
+            KnownResource::User(user) => Resource::User(user),
+            KnownResource::Playlist(playlist) => Resource::Playlist(playlist),
+            KnownResource::Comment(comment) => Resource::Comment(comment),
+        };
+
+        Ok(resource)
+    }
 }
 
 impl Client {
@@ -125,15 +206,25 @@ impl Client {
     /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
     /// ```
     pub fn new(client_id: &str, handle: &tokio_core::reactor::Handle) -> Client {
-        let client = hyper::Client::configure()
-            .connector(hyper_tls::HttpsConnector::new(4, &handle).unwrap())
-            .build(&handle);
-        // client.set_redirect_policy(hyper::client::RedirectPolicy::FollowNone);
+        ClientBuilder::new(client_id).build(handle).unwrap()
+    }
 
-        Client {
-            client_id: client_id.to_owned(),
-            http_client: client,
-        }
+    /// Returns a builder for a `Client` with the provided `client_id`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::builder(env!("SOUNDCLOUD_CLIENT_ID"))
+    ///     .scheme("http")
+    ///     .host("localhost")
+    ///     .port(Some(8080))
+    ///     .build(&handle)
+    ///     .unwrap();
+    /// ```
+    pub fn builder(client_id: &str) -> ClientBuilder {
+        ClientBuilder::new(client_id)
     }
 
     /// Returns the client id.
@@ -141,34 +232,231 @@ impl Client {
         &self.client_id
     }
 
+    /// Returns the current access token, if the client is authenticated.
+    pub fn access_token(&self) -> Option<AccessToken> {
+        self.access_token.borrow().clone()
+    }
+
+    /// Sets or clears the access token that is sent with every API request.
+    pub fn set_access_token(&self, access_token: Option<AccessToken>) {
+        *self.access_token.borrow_mut() = access_token;
+    }
+
+    /// Returns the url that users should visit to authorize the application.
+    ///
+    /// After authorizing, the user is redirected to the configured redirect uri with a `code`
+    /// query parameter, which can be passed to `exchange_code`.
+    pub fn authorize_url(&self, scope: Option<&str>, state: Option<&str>) -> Result<Url> {
+        let redirect_uri = match self.redirect_uri {
+            Some(ref redirect_uri) => redirect_uri,
+            None => return Err(Error::MissingCredentials("redirect_uri".to_owned())),
+        };
+
+        let mut url = Url::parse(oauth::CONNECT_URL)?;
+
+        {
+            let mut query_pairs = url.query_pairs_mut();
+
+            query_pairs
+                .append_pair("client_id", &self.client_id)
+                .append_pair("redirect_uri", redirect_uri)
+                .append_pair("response_type", "code");
+
+            if let Some(scope) = scope {
+                query_pairs.append_pair("scope", scope);
+            }
+
+            if let Some(state) = state {
+                query_pairs.append_pair("state", state);
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// Exchanges an authorization code for an access token.
+    ///
+    /// On success the access token is stored in the client and used for subsequent requests.
+    pub fn exchange_code<'a>(
+        &'a self,
+        code: &str,
+    ) -> Box<Future<Item = AccessToken, Error = Error> + 'a> {
+        self.request_token(Grant::AuthorizationCode(code.to_owned()))
+    }
+
+    /// Authenticates as the application itself using the client credentials grant.
+    ///
+    /// On success the access token is stored in the client and used for subsequent requests.
+    pub fn authenticate<'a>(&'a self) -> Box<Future<Item = AccessToken, Error = Error> + 'a> {
+        self.request_token(Grant::ClientCredentials)
+    }
+
+    /// Exchanges the refresh token of the current access token for a new access token.
+    pub fn refresh_access_token<'a>(
+        &'a self,
+    ) -> Box<Future<Item = AccessToken, Error = Error> + 'a> {
+        let refresh_token = self
+            .access_token
+            .borrow()
+            .as_ref()
+            .and_then(|token| token.refresh_token.clone());
+
+        match refresh_token {
+            Some(refresh_token) => self.request_token(Grant::RefreshToken(refresh_token)),
+            None => Box::new(future::err(Error::MissingCredentials(
+                "refresh_token".to_owned(),
+            ))),
+        }
+    }
+
+    /// Sends a request for an access token to the `/oauth2/token` endpoint.
+    ///
+    /// On success the access token is stored in the client and used for subsequent requests.
+    pub fn request_token<'a>(
+        &'a self,
+        grant: Grant,
+    ) -> Box<Future<Item = AccessToken, Error = Error> + 'a> {
+        let form = match self.token_form(&grant) {
+            Ok(form) => form,
+            Err(error) => return Box::new(future::err(error)),
+        };
+
+        let uri = match self.api_url("/oauth2/token").as_str().parse() {
+            Ok(uri) => uri,
+            Err(error) => return Box::new(future::err(Error::UriError(error))),
+        };
+
+        let mut request = hyper::Request::new(hyper::Method::Post, uri);
+        request
+            .headers_mut()
+            .set(hyper::header::ContentType::form_url_encoded());
+        request.set_body(form);
+
+        let token = self
+            .http_client
+            .request(request)
+            .and_then(|response| response.body().concat2())
+            .map_err(|error| Error::HttpError(error))
+            .and_then(|body| oauth::parse_token_response(&body))
+            .map(move |token| {
+                self.set_access_token(Some(token.clone()));
+                token
+            });
+
+        Box::new(token)
+    }
+
+    /// Returns the form-encoded request body for exchanging `grant` for an access token.
+    fn token_form(&self, grant: &Grant) -> Result<String> {
+        let client_secret = match self.client_secret {
+            Some(ref client_secret) => client_secret,
+            None => return Err(Error::MissingCredentials("client_secret".to_owned())),
+        };
+
+        let mut form = form_urlencoded::Serializer::new(String::new());
+
+        form.append_pair("client_id", &self.client_id)
+            .append_pair("client_secret", client_secret)
+            .append_pair("grant_type", grant.grant_type());
+
+        match *grant {
+            Grant::AuthorizationCode(ref code) => {
+                let redirect_uri = match self.redirect_uri {
+                    Some(ref redirect_uri) => redirect_uri,
+                    None => return Err(Error::MissingCredentials("redirect_uri".to_owned())),
+                };
+
+                form.append_pair("redirect_uri", redirect_uri)
+                    .append_pair("code", code);
+            }
+            Grant::RefreshToken(ref refresh_token) => {
+                form.append_pair("refresh_token", refresh_token);
+            }
+            Grant::ClientCredentials => {}
+        }
+
+        Ok(form.finish())
+    }
+
+    /// Returns the policy for retrying requests that failed with a transient error.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Returns the maximum number of redirects followed for a single request.
+    pub fn max_redirects(&self) -> usize {
+        self.max_redirects
+    }
+
+    /// Returns the rate limiter that API requests are queued through, if one is configured.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Returns statistics about how long API requests have waited for the rate limiter, if one is
+    /// configured.
+    pub fn rate_limit_metrics(&self) -> Option<RateLimitMetrics> {
+        self.rate_limiter.as_ref().map(|limiter| limiter.metrics())
+    }
+
+    /// Returns the base url that API requests are sent to.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Returns the url for the API endpoint at `path`.
+    pub fn api_url(&self, path: &str) -> Url {
+        self.base_url.join(path).unwrap()
+    }
+
+    /// Rewrites urls pointing at the API host to point at the configured base url instead.
+    ///
+    /// Urls on any other host, such as CDN redirects, are returned unchanged.
+    pub fn rebase_url(&self, url: Url) -> Url {
+        rebase_url(&self.base_url, url)
+    }
+
     /// Creates and sends a HTTP GET request to the API endpoint.
     ///
-    /// A `client_id` parameter will automatically be added to the request.
+    /// A `client_id` parameter will automatically be added to the request, as well as an
+    /// `Authorization` header if the client has an access token.
+    ///
+    /// The request is sent through `fetch`, so it is retried and rate limited like every other
+    /// request. Returns the HTTP response if it is successful or a redirect, an `Error::Status`
+    /// otherwise.
     ///
-    /// Returns the HTTP response on success, an error otherwise.
+    /// This used to return a `hyper::client::FutureResponse` that resolved to the response
+    /// whatever its status. Check `Error::status` to handle unsuccessful responses.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::io::Read;
-    /// use soundcloud::Client;
-    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// use futures::{Future, Stream};
+    /// use soundcloud::{Client, Error};
+    ///
+    /// let mut core = tokio_core::reactor::Core::new().unwrap();
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"), &core.handle());
     /// let response = client.get("/resolve", Some(&[("url",
     /// "https://soundcloud.com/firepowerrecs/afk-shellshock-kamikaze-promo-mix-lock-load-series-vol-20")]));
     ///
-    /// let mut buffer = String::new();
-    /// response.unwrap().read_to_string(&mut buffer);
+    /// let body = core.run(response.and_then(|response| {
+    ///     response.body().concat2().map_err(Error::HttpError)
+    /// })).unwrap();
     ///
-    /// assert!(!buffer.is_empty());
+    /// assert!(!body.is_empty());
     /// ```
-    pub fn get<I, K, V>(&self, path: &str, params: Option<I>) -> hyper::client::FutureResponse
+    pub fn get<I, K, V>(
+        &self,
+        path: &str,
+        params: Option<I>,
+    ) -> Box<Future<Item = hyper::Response, Error = Error>>
     where
         I: IntoIterator,
         I::Item: Borrow<(K, V)>,
         K: AsRef<str>,
         V: AsRef<str>,
     {
-        let mut url = Url::parse(&format!("https://{}{}", super::API_HOST, path)).unwrap();
+        let mut url = self.api_url(path);
 
         {
             let mut query_pairs = url.query_pairs_mut();
@@ -178,48 +466,156 @@ impl Client {
             }
         }
 
-        let uri = self.parse_url(url).unwrap();
-        self.http_client.get(uri)
+        match self.parse_url(url) {
+            Ok(uri) => self.fetch(hyper::Method::Get, uri, hyper::Headers::new(), None),
+            Err(error) => Box::new(future::err(error)),
+        }
+    }
+
+    /// Creates and sends a HTTP GET request to the API resource at `url` and deserializes the JSON
+    /// response body.
+    pub fn get_json<T>(&self, url: &str) -> Box<Future<Item = T, Error = Error>>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        self.fetch_json(hyper::Method::Get, url, hyper::Headers::new(), None)
+    }
+
+    /// Sends a HTTP request to the API resource at `url` and deserializes the JSON response body.
+    fn fetch_json<T>(
+        &self,
+        method: hyper::Method,
+        url: &str,
+        headers: hyper::Headers,
+        body: Option<String>,
+    ) -> Box<Future<Item = T, Error = Error>>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let uri = match self.parse_url(url) {
+            Ok(uri) => uri,
+            Err(error) => return Box::new(future::err(error)),
+        };
+
+        let response = self
+            .fetch(method, uri, headers, body)
+            .and_then(|response| {
+                response
+                    .body()
+                    .concat2()
+                    .map_err(|error| Error::HttpError(error))
+            })
+            .and_then(|body| {
+                serde_json::from_slice(&body).map_err(|error| Error::JsonError(error))
+            });
+
+        Box::new(response)
+    }
+
+    /// Sends a HTTP request, and sends it again according to the retry policy if it fails with a
+    /// transient error and `method` is idempotent.
+    ///
+    /// Requests to the API are sent with an `Authorization` header if the client has an access
+    /// token, and every attempt waits for the rate limiter if one is configured.
+    ///
+    /// Returns the HTTP response if it is successful or a redirect, an `Error::Status` otherwise.
+    /// This replaces `send`, which passed the request to hyper as it was.
+    pub fn fetch(
+        &self,
+        method: hyper::Method,
+        uri: hyper::Uri,
+        mut headers: hyper::Headers,
+        body: Option<String>,
+    ) -> Box<Future<Item = hyper::Response, Error = Error>> {
+        let mut rate_limiter = None;
+
+        if self.is_api_uri(&uri) {
+            if let Some(ref token) = *self.access_token.borrow() {
+                headers.set(hyper::header::Authorization(token.authorization()));
+            }
+
+            rate_limiter = self.rate_limiter.clone();
+        }
+
+        let retry_policy = if retry::is_idempotent(&method) {
+            self.retry_policy.clone()
+        } else {
+            RetryPolicy::none()
+        };
+
+        let http_client = self.http_client.clone();
+        let handle = self.handle.clone();
+        let url = uri.to_string();
+
+        retry::retry(&self.handle, &retry_policy, move || {
+            let permit = match rate_limiter {
+                Some(ref limiter) => limiter.acquire(uri.path(), &handle),
+                None => Box::new(future::ok(())),
+            };
+
+            let mut request = hyper::Request::new(method.clone(), uri.clone());
+            *request.headers_mut() = headers.clone();
+
+            if let Some(ref body) = body {
+                request.set_body(body.clone());
+            }
+
+            let url = url.clone();
+            let http_client = http_client.clone();
+
+            permit.and_then(move |_| {
+                http_client
+                    .request(request)
+                    .map_err(|error| Error::HttpError(error))
+                    .and_then(move |response| check_status(url, response))
+            })
+        })
+    }
+
+    /// Returns true if `uri` points at the configured API base url.
+    fn is_api_uri(&self, uri: &hyper::Uri) -> bool {
+        match Url::parse(&uri.to_string()) {
+            Ok(url) => url.origin() == self.base_url.origin(),
+            Err(_) => false,
+        }
     }
 
     pub fn download<'a, 'b, W: 'a + Write>(
         &'a self,
         track: &'b Track,
-        mut writer: W,
+        writer: W,
     ) -> Box<Future<Item = usize, Error = Error> + 'a> {
-        if !track.downloadable || !track.download_url.is_some() {
-            return Box::new(future::err(Error::TrackNotDownloadable));
-        }
+        self.download_with_progress(track, writer, |_| ())
+    }
 
-        let url = self
-            .parse_url(track.download_url.as_ref().unwrap())
-            .unwrap();
+    /// Like `download`, but calls `on_progress` after every chunk that is written.
+    pub fn download_with_progress<'a, 'b, W, F>(
+        &'a self,
+        track: &'b Track,
+        writer: W,
+        on_progress: F,
+    ) -> Box<Future<Item = usize, Error = Error> + 'a>
+    where
+        W: 'a + Write,
+        F: 'a + FnMut(Progress),
+    {
+        let response = self
+            .download_response(track)
+            .and_then(move |response| write_body(response, writer, on_progress));
+
+        Box::new(response)
+    }
 
+    /// Like `download`, but writes to an asynchronous `writer` so that slow sinks don't block the
+    /// event loop.
+    pub fn download_async<'a, 'b, W: 'a + AsyncWrite>(
+        &'a self,
+        track: &'b Track,
+        writer: W,
+    ) -> Box<Future<Item = usize, Error = Error> + 'a> {
         let response = self
-            .http_client
-            .get(url)
-            .and_then(move |response| {
-                // Follow the redirect just this once.
-                if let Some(header) = response.headers().get::<hyper::header::Location>().cloned() {
-                    let uri = header.parse().unwrap();
-                    let inner_response = self.http_client.get(uri);
-                    Either::A(inner_response)
-                } else {
-                    Either::B(future::ok(response))
-                }
-            })
-            .map(move |response| {
-                response.body().fold(0, move |acc, chunk| {
-                    let result = match writer.write(chunk.as_ref()) {
-                        Ok(num_written) => Ok(acc + num_written),
-                        error => error,
-                    };
-
-                    result
-                })
-            })
-            .flatten()
-            .map_err(|error| Error::HttpError(error));
+            .download_response(track)
+            .and_then(move |response| write_body_async(response, writer));
 
         Box::new(response)
     }
@@ -229,118 +625,1745 @@ impl Client {
     pub fn stream<'a, 'b, W: 'a + Write>(
         &'a self,
         track: &'b Track,
-        mut writer: W,
+        writer: W,
     ) -> Box<Future<Item = usize, Error = Error> + 'a> {
-        if !track.streamable || !track.stream_url.is_some() {
-            return Box::new(future::err(Error::TrackNotStreamable));
-        }
+        self.stream_with_progress(track, writer, |_| ())
+    }
+
+    /// Like `stream`, but calls `on_progress` after every chunk that is written.
+    pub fn stream_with_progress<'a, 'b, W, F>(
+        &'a self,
+        track: &'b Track,
+        writer: W,
+        on_progress: F,
+    ) -> Box<Future<Item = usize, Error = Error> + 'a>
+    where
+        W: 'a + Write,
+        F: 'a + FnMut(Progress),
+    {
+        let response = self
+            .stream_response(track)
+            .and_then(move |response| write_body(response, writer, on_progress));
 
-        let url = self.parse_url(track.stream_url.as_ref().unwrap()).unwrap();
+        Box::new(response)
+    }
 
+    /// Like `stream`, but writes to an asynchronous `writer` so that slow sinks don't block the
+    /// event loop.
+    pub fn stream_async<'a, 'b, W: 'a + AsyncWrite>(
+        &'a self,
+        track: &'b Track,
+        writer: W,
+    ) -> Box<Future<Item = usize, Error = Error> + 'a> {
         let response = self
-            .http_client
-            .get(url)
-            .and_then(move |response| {
-                // Follow the redirect just this once.
-                if let Some(header) = response.headers().get::<hyper::header::Location>().cloned() {
-                    let uri = header.parse().unwrap();
-                    let inner_response = self.http_client.get(uri);
-                    Either::A(inner_response)
-                } else {
-                    Either::B(future::ok(response))
+            .stream_response(track)
+            .and_then(move |response| write_body_async(response, writer));
+
+        Box::new(response)
+    }
+
+    /// Returns the track provided in the tracks `download_url` as a stream of byte chunks if the
+    /// track is downloadable via the API.
+    pub fn download_bytes<'a>(
+        &'a self,
+        track: &Track,
+    ) -> Box<Stream<Item = Bytes, Error = Error> + 'a> {
+        Box::new(
+            self.download_response(track)
+                .map(body_bytes)
+                .flatten_stream(),
+        )
+    }
+
+    /// Returns the track provided in the tracks `stream_url` as a stream of byte chunks if the
+    /// track is streamable via the API.
+    pub fn stream_bytes<'a>(
+        &'a self,
+        track: &Track,
+    ) -> Box<Stream<Item = Bytes, Error = Error> + 'a> {
+        Box::new(self.stream_response(track).map(body_bytes).flatten_stream())
+    }
+
+    /// Streams the audio of `transcoding` to the `writer`, concatenating the segments of HLS
+    /// transcodings.
+    pub fn stream_transcoding<'a, W: 'a + Write>(
+        &'a self,
+        transcoding: &Transcoding,
+        writer: W,
+    ) -> Box<Future<Item = usize, Error = Error> + 'a> {
+        write_bytes(self.transcoding_bytes(transcoding), writer)
+    }
+
+    /// Returns the audio of `transcoding` as a stream of byte chunks, fetching the segments of
+    /// HLS transcodings one after another.
+    pub fn transcoding_bytes<'a>(
+        &'a self,
+        transcoding: &Transcoding,
+    ) -> Box<Stream<Item = Bytes, Error = Error> + 'a> {
+        let protocol = transcoding.protocol();
+
+        let bytes = self
+            .get_json::<MediaLocation>(&transcoding.url)
+            .and_then(move |location| {
+                let uri = location.url.parse()?;
+
+                match protocol {
+                    Protocol::Hls => Ok(self.hls_bytes(uri)),
+                    Protocol::Progressive => Ok(self.response_bytes(uri)),
+                    Protocol::Unknown => Err(Error::ApiError(
+                        "unsupported transcoding protocol".to_owned(),
+                    )),
                 }
             })
-            .map(move |response| {
-                response.body().fold(0, move |acc, chunk| {
-                    let result = match writer.write(chunk.as_ref()) {
-                        Ok(num_written) => Ok(acc + num_written),
-                        error => error,
-                    };
-
-                    result
-                })
-            })
-            .flatten()
-            .map_err(|error| Error::HttpError(error));
+            .flatten_stream();
 
-        Box::new(response)
+        Box::new(bytes)
     }
 
-    /// Resolves any soundcloud resource and returns it as a `Url`.
-    pub fn resolve(&self, url: &str) -> Box<Future<Item = Url, Error = Error>> {
-        let uri = self.get("/resolve", Some(&[("url", url)]));
+    /// Fetches the HLS media playlist at `uri` and returns its segments as one stream of byte
+    /// chunks.
+    fn hls_bytes<'a>(&'a self, uri: hyper::Uri) -> Box<Stream<Item = Bytes, Error = Error> + 'a> {
+        let base_url = match Url::parse(&uri.to_string()) {
+            Ok(url) => url,
+            Err(error) => return Box::new(stream::once(Err(Error::ParseError(error)))),
+        };
 
-        let response = uri
-            .map_err(|error| Error::HttpError(error))
-            .and_then(
-                |response| match response.headers().get::<hyper::header::Location>() {
-                    Some(header) => future::ok(Url::parse(header.as_ref()).unwrap()),
-                    _ => future::err(Error::ApiError("expected location header".to_owned())),
-                },
-            );
+        let bytes = self
+            .get_redirected(uri, hyper::Headers::new())
+            .and_then(|response| {
+                response
+                    .body()
+                    .concat2()
+                    .map_err(|error| Error::HttpError(error))
+            })
+            .and_then(move |body| hls::parse_playlist(&base_url, &String::from_utf8_lossy(&body)))
+            .map(move |segments| {
+                stream::iter_ok::<_, Error>(segments)
+                    .map(move |segment| match segment.url.as_str().parse() {
+                        Ok(uri) => self.segment_bytes(uri, segment.range),
+                        Err(error) => Box::new(stream::once(Err(Error::UriError(error)))),
+                    })
+                    .flatten()
+            })
+            .flatten_stream();
 
-        Box::new(response)
+        Box::new(bytes)
     }
 
-    /// Returns a builder for a single track-by-id request.
+    /// Fetches the image at `url` in the given `size` and returns its bytes.
     ///
     /// # Examples
     ///
     /// ```
-    /// use soundcloud::Client;
+    /// use soundcloud::{Client, ImageSize};
     ///
     /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
-    /// let track = client.track(262681089).get();
-    ///
-    /// assert_eq!(track.unwrap().id, 262681089);
+    /// let track = client.track(262681089).get().wait().unwrap();
+    /// let artwork = track.artwork_url.unwrap();
+    /// let thumbnail = client.fetch_image(&artwork, ImageSize::Badge).wait().unwrap();
     /// ```
-    pub fn track(&self, id: usize) -> SingleTrackRequestBuilder {
-        SingleTrackRequestBuilder::new(self, id)
+    pub fn fetch_image<'a>(
+        &'a self,
+        url: &ArtworkUrl,
+        size: ImageSize,
+    ) -> Box<Future<Item = Bytes, Error = Error> + 'a> {
+        Box::new(self.get_body(&url.size(size)).map(Bytes::from))
     }
 
-    /// Returns a builder for searching tracks with multiple criteria.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use soundcloud::Client;
-    ///
-    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
-    /// let tracks = client.tracks().genres(Some(["HipHop"])).get();
+    /// Fetches the waveform of `track`.
     ///
-    /// assert!(tracks.unwrap().expect("no tracks found").len() > 0);
-    /// ```
-    pub fn tracks(&self) -> TrackRequestBuilder {
-        TrackRequestBuilder::new(self)
-    }
+    /// The JSON representation of the waveform is used if it is available, the waveform image is
+    /// decoded otherwise.
+    pub fn waveform<'a>(
+        &'a self,
+        track: &Track,
+    ) -> Box<Future<Item = Waveform, Error = Error> + 'a> {
+        let image_url = track.waveform_url.clone();
 
-    /// Parses a string and returns a url with the client_id query parameter set.
-    pub fn parse_url<S: AsRef<str>>(&self, url: S) -> Result<hyper::Uri> {
-        let mut url = Url::parse(url.as_ref()).unwrap();
-        url.query_pairs_mut()
-            .append_pair("client_id", &self.client_id);
-        url.as_str().parse().map_err(|error| Error::UriError(error))
-    }
-}
+        let json_url = match waveform::json_url(&image_url) {
+            Some(json_url) => json_url,
+            None => image_url.clone(),
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use url::Url;
+        let waveform = self
+            .get_body(&json_url)
+            .and_then(|body| serde_json::from_slice(&body).map_err(|error| Error::JsonError(error)))
+            .or_else(move |error| {
+                if json_url == image_url {
+                    return Either::A(future::err(error));
+                }
 
-    fn client(handle: &tokio_core::reactor::Handle) -> Client {
-        Client::new(env!("SOUNDCLOUD_CLIENT_ID"), handle)
+                Either::B(
+                    self.get_body(&image_url)
+                        .and_then(|body| Waveform::from_png(&body)),
+                )
+            });
+
+        Box::new(waveform)
     }
 
-    #[test]
-    fn test_resolve_track() {
-        let mut core = tokio_core::reactor::Core::new().unwrap();
+    /// Sends a GET request to `url`, following redirects, and returns the response body.
+    fn get_body<'a>(&'a self, url: &str) -> Box<Future<Item = hyper::Chunk, Error = Error> + 'a> {
+        let uri = match url.parse() {
+            Ok(uri) => uri,
+            Err(error) => return Box::new(future::err(Error::UriError(error))),
+        };
 
-        let work = client(&core.handle()).resolve("https://soundcloud.com/isqa/tree-eater-1");
+        let body = self
+            .get_redirected(uri, hyper::Headers::new())
+            .and_then(|response| {
+                response
+                    .body()
+                    .concat2()
+                    .map_err(|error| Error::HttpError(error))
+            });
 
-        let result = core.run(work);
+        Box::new(body)
+    }
 
-        assert_eq!(
+    /// Writes ID3v2 tags with the metadata of `track` to the MP3 file at `path`, with the artwork
+    /// of the track as the front cover if it has any.
+    ///
+    /// The artwork is fetched at 500x500 pixels, or at the size the API links to if there is no
+    /// such rendition. The cover is left out if neither exists.
+    #[cfg(feature = "id3")]
+    pub fn tag_file<'a, P: AsRef<Path>>(
+        &'a self,
+        path: P,
+        track: &Track,
+    ) -> Box<Future<Item = (), Error = Error> + 'a> {
+        let path = path.as_ref().to_path_buf();
+        let track = track.clone();
+
+        let artwork = match track.artwork_url {
+            Some(ref url) => self.cover_artwork(url),
+            None => Box::new(future::ok(None)),
+        };
+
+        let tagged = artwork.and_then(move |artwork| {
+            tag::write_tags(
+                &path,
+                &track,
+                artwork.as_ref().map(|artwork| artwork.as_ref()),
+            )
+        });
+
+        Box::new(tagged)
+    }
+
+    /// Fetches artwork for a cover at 500x500 pixels, falling back to the size the API links to,
+    /// or returns `None` if neither exists.
+    #[cfg(feature = "id3")]
+    fn cover_artwork<'a>(
+        &'a self,
+        url: &ArtworkUrl,
+    ) -> Box<Future<Item = Option<Bytes>, Error = Error> + 'a> {
+        let linked_url = url.as_str().to_owned();
+
+        let artwork = self
+            .fetch_image(url, ImageSize::T500x500)
+            .or_else(move |error| {
+                if !error.is_not_found() {
+                    return Either::A(future::err(error));
+                }
+
+                Either::B(self.get_body(&linked_url).map(Bytes::from))
+            })
+            .map(Some)
+            .or_else(|error| {
+                if error.is_not_found() {
+                    Ok(None)
+                } else {
+                    Err(error)
+                }
+            });
+
+        Box::new(artwork)
+    }
+
+    /// Returns the body of a GET request to `uri`, following redirects, as a stream of byte
+    /// chunks.
+    fn response_bytes<'a>(
+        &'a self,
+        uri: hyper::Uri,
+    ) -> Box<Stream<Item = Bytes, Error = Error> + 'a> {
+        let bytes = self
+            .get_redirected(uri, hyper::Headers::new())
+            .map(body_bytes)
+            .flatten_stream();
+
+        Box::new(bytes)
+    }
+
+    /// Returns the bytes of an HLS segment, requesting only its byte `range` if it has one.
+    ///
+    /// Fails with `Error::UnexpectedRange` if the server ignores the range, as the segment would
+    /// otherwise be corrupted by the rest of the resource.
+    fn segment_bytes<'a>(
+        &'a self,
+        uri: hyper::Uri,
+        range: Option<hls::ByteRange>,
+    ) -> Box<Stream<Item = Bytes, Error = Error> + 'a> {
+        let range = match range {
+            Some(range) => range,
+            None => return self.response_bytes(uri),
+        };
+
+        let mut headers = hyper::Headers::new();
+        headers.set(hyper::header::Range::Bytes(vec![
+            hyper::header::ByteRangeSpec::FromTo(range.offset, range.last()),
+        ]));
+
+        let bytes = self
+            .get_redirected(uri, headers)
+            .and_then(move |response| {
+                if response.status() != hyper::StatusCode::PartialContent {
+                    return Err(Error::UnexpectedRange(format!(
+                        "expected bytes {}-{}, got status {}",
+                        range.offset,
+                        range.last(),
+                        response.status()
+                    )));
+                }
+
+                Ok(body_bytes(response))
+            })
+            .flatten_stream();
+
+        Box::new(bytes)
+    }
+
+    /// Requests the track provided in the tracks `download_url` if the track is downloadable.
+    fn download_response<'a>(
+        &'a self,
+        track: &Track,
+    ) -> Box<Future<Item = hyper::Response, Error = Error> + 'a> {
+        if !track.downloadable || !track.download_url.is_some() {
+            return Box::new(future::err(Error::TrackNotDownloadable));
+        }
+
+        let url = match self.parse_url(track.download_url.as_ref().unwrap()) {
+            Ok(url) => url,
+            Err(error) => return Box::new(future::err(error)),
+        };
+
+        self.get_redirected(url, hyper::Headers::new())
+    }
+
+    /// Requests the track provided in the tracks `stream_url` if the track is streamable.
+    fn stream_response<'a>(
+        &'a self,
+        track: &Track,
+    ) -> Box<Future<Item = hyper::Response, Error = Error> + 'a> {
+        if !track.streamable || !track.stream_url.is_some() {
+            return Box::new(future::err(Error::TrackNotStreamable));
+        }
+
+        let url = match self.parse_url(track.stream_url.as_ref().unwrap()) {
+            Ok(url) => url,
+            Err(error) => return Box::new(future::err(error)),
+        };
+
+        self.get_redirected(url, hyper::Headers::new())
+    }
+
+    /// Downloads the track provided in the tracks `download_url` to the file at `path`, resuming
+    /// the download if the file already contains the start of the track.
+    ///
+    /// If the file isn't empty, only the remaining bytes are requested with a `Range` header and
+    /// appended to the file. If the server ignores the range, or the file doesn't match the length
+    /// of the track, the file is truncated and downloaded from the start. Returns the size of the
+    /// complete file.
+    pub fn download_to_file<'a, P: AsRef<Path>>(
+        &'a self,
+        track: &Track,
+        path: P,
+    ) -> Box<Future<Item = u64, Error = Error> + 'a> {
+        if !track.downloadable || !track.download_url.is_some() {
+            return Box::new(future::err(Error::TrackNotDownloadable));
+        }
+
+        match self.parse_url(track.download_url.as_ref().unwrap()) {
+            Ok(url) => self.resume_to_file(url, path.as_ref()),
+            Err(error) => Box::new(future::err(error)),
+        }
+    }
+
+    /// Streams the track provided in the tracks `stream_url` to the file at `path`, resuming the
+    /// stream if the file already contains the start of the track, like `download_to_file`.
+    /// Returns the size of the complete file.
+    pub fn stream_to_file<'a, P: AsRef<Path>>(
+        &'a self,
+        track: &Track,
+        path: P,
+    ) -> Box<Future<Item = u64, Error = Error> + 'a> {
+        if !track.streamable || !track.stream_url.is_some() {
+            return Box::new(future::err(Error::TrackNotStreamable));
+        }
+
+        match self.parse_url(track.stream_url.as_ref().unwrap()) {
+            Ok(url) => self.resume_to_file(url, path.as_ref()),
+            Err(error) => Box::new(future::err(error)),
+        }
+    }
+
+    /// Appends the body of `url` to the file at `path`, requesting only the bytes that are
+    /// missing from the file. Returns the size of the complete file.
+    fn resume_to_file<'a>(
+        &'a self,
+        url: hyper::Uri,
+        path: &Path,
+    ) -> Box<Future<Item = u64, Error = Error> + 'a> {
+        let file = match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => file,
+            Err(error) => return Box::new(future::err(Error::Io(error))),
+        };
+
+        let offset = match file.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(error) => return Box::new(future::err(Error::Io(error))),
+        };
+
+        let mut headers = hyper::Headers::new();
+
+        if offset > 0 {
+            headers.set(hyper::header::Range::Bytes(vec![
+                hyper::header::ByteRangeSpec::AllFrom(offset),
+            ]));
+        }
+
+        let response = self.get_redirected(url.clone(), headers).then(
+            move |result| -> Box<Future<Item = u64, Error = Error> + 'a> {
+                match result {
+                    Ok(response) => write_range(response, file, offset),
+                    Err(Error::Status(ref error))
+                        if offset > 0 && error.status == hyper::StatusCode::RangeNotSatisfiable =>
+                    {
+                        match complete_length(&error.headers) {
+                            // The file already holds every byte of the track.
+                            Some(length) if length == offset => Box::new(future::ok(offset)),
+                            // The file doesn't match the track, e.g. a stale copy of a replaced
+                            // upload, so download the whole track again.
+                            _ => {
+                                if let Err(error) = file.set_len(0) {
+                                    return Box::new(future::err(Error::Io(error)));
+                                }
+
+                                let response = self
+                                    .get_redirected(url, hyper::Headers::new())
+                                    .and_then(move |response| write_range(response, file, 0));
+
+                                Box::new(response)
+                            }
+                        }
+                    }
+                    Err(error) => Box::new(future::err(error)),
+                }
+            },
+        );
+
+        Box::new(response)
+    }
+
+    /// Sends a GET request with `headers` to the API resource at `uri`, following redirects up to
+    /// the configured maximum.
+    ///
+    /// Fails with `Error::RedirectLoop` if a url is visited twice, `Error::TooManyRedirects` if
+    /// the maximum is exceeded and `Error::Status` if the final response isn't successful.
+    fn get_redirected<'a>(
+        &'a self,
+        uri: hyper::Uri,
+        headers: hyper::Headers,
+    ) -> Box<Future<Item = hyper::Response, Error = Error> + 'a> {
+        let url = match Url::parse(&uri.to_string()) {
+            Ok(url) => url,
+            Err(error) => return Box::new(future::err(Error::ParseError(error))),
+        };
+
+        let response =
+            future::loop_fn((url, vec![]), move |(url, mut visited): (Url, Vec<Url>)| {
+                let uri = match url.as_str().parse() {
+                    Ok(uri) => uri,
+                    Err(error) => return Either::A(future::err(Error::UriError(error))),
+                };
+
+                let response = self
+                    .fetch(hyper::Method::Get, uri, headers.clone(), None)
+                    .and_then(move |response| {
+                        let location = match redirect_location(&self.base_url, &url, &response) {
+                            Some(location) => location?,
+                            None => return Ok(Loop::Break(response)),
+                        };
+
+                        visited.push(url);
+
+                        if visited.contains(&location) {
+                            return Err(Error::RedirectLoop(location.into_string()));
+                        }
+
+                        if visited.len() > self.max_redirects {
+                            return Err(Error::TooManyRedirects(self.max_redirects));
+                        }
+
+                        Ok(Loop::Continue((location, visited)))
+                    });
+
+                Either::B(response)
+            });
+
+        Box::new(response)
+    }
+
+    /// Resolves any soundcloud resource and returns it as a `Url`.
+    pub fn resolve(&self, url: &str) -> Box<Future<Item = Url, Error = Error>> {
+        let uri = self.get("/resolve", Some(&[("url", url)]));
+        let base_url = self.base_url.clone();
+        let request_url = self.api_url("/resolve");
+
+        let response = uri.and_then(move |response| {
+            match redirect_location(&base_url, &request_url, &response) {
+                Some(location) => location,
+                None => Err(Error::ApiError("expected location header".to_owned())),
+            }
+        });
+
+        Box::new(response)
+    }
+
+    /// Resolves any soundcloud resource and returns the resource itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::{Client, Resource};
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let resource = client.resolve_resource("https://soundcloud.com/isqa/tree-eater-1");
+    ///
+    /// match resource.wait().unwrap() {
+    ///     Resource::Track(track) => assert_eq!(track.id, 262976655),
+    ///     _ => panic!("expected a track"),
+    /// }
+    /// ```
+    pub fn resolve_resource<'a>(
+        &'a self,
+        url: &str,
+    ) -> Box<Future<Item = Resource, Error = Error> + 'a> {
+        let resource = self
+            .resolve(url)
+            .and_then(move |url| self.get_json(url.as_str()));
+
+        Box::new(resource)
+    }
+
+    /// Returns a builder for a single track-by-id request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let track = client.track(262681089).get();
+    ///
+    /// assert_eq!(track.unwrap().id, 262681089);
+    /// ```
+    pub fn track(&self, id: usize) -> SingleTrackRequestBuilder {
+        SingleTrackRequestBuilder::new(self, id)
+    }
+
+    /// Returns a builder for searching tracks with multiple criteria.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let tracks = client.tracks().genres(Some(["HipHop"])).get();
+    ///
+    /// assert!(tracks.unwrap().expect("no tracks found").len() > 0);
+    /// ```
+    pub fn tracks(&self) -> TrackRequestBuilder {
+        TrackRequestBuilder::new(self)
+    }
+
+    /// Returns a builder for a single user-by-id request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let user = client.user(3207).get();
+    ///
+    /// assert_eq!(user.unwrap().id, 3207);
+    /// ```
+    pub fn user(&self, id: usize) -> SingleUserRequestBuilder {
+        SingleUserRequestBuilder::new(self, id)
+    }
+
+    /// Returns a builder for searching users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let users = client.users().query(Some("noisia")).get();
+    ///
+    /// assert!(users.unwrap().expect("no users found").len() > 0);
+    /// ```
+    pub fn users(&self) -> UserRequestBuilder {
+        UserRequestBuilder::new(self)
+    }
+
+    /// Returns a builder for a single playlist-by-id request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let playlist = client.playlist(405726).get();
+    ///
+    /// assert_eq!(playlist.unwrap().id, 405726);
+    /// ```
+    pub fn playlist(&self, id: usize) -> SinglePlaylistRequestBuilder {
+        SinglePlaylistRequestBuilder::new(self, id)
+    }
+
+    /// Returns a builder for searching playlists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let playlists = client.playlists().query(Some("noisia")).get();
+    ///
+    /// assert!(playlists.unwrap().expect("no playlists found").len() > 0);
+    /// ```
+    pub fn playlists(&self) -> PlaylistRequestBuilder {
+        PlaylistRequestBuilder::new(self)
+    }
+
+    /// Requests a single comment by its id.
+    pub fn comment(&self, id: usize) -> Box<Future<Item = Comment, Error = Error>> {
+        let url = self.api_url(&format!("/comments/{}", id));
+
+        self.get_json(url.as_str())
+    }
+
+    /// Returns a stream of the comments on the track with the id `track_id`, following the
+    /// pagination cursors until the results are exhausted or `max_items` comments have been
+    /// returned.
+    pub fn track_comments<'a>(
+        &'a self,
+        track_id: usize,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = Comment, Error = Error> + 'a> {
+        let mut url = self.api_url(&format!("/tracks/{}/comments", track_id));
+
+        url.query_pairs_mut()
+            .append_pair("limit", &DEFAULT_PAGE_SIZE.to_string())
+            .append_pair("linked_partitioning", "1");
+
+        page::paginate(self, url, max_items)
+    }
+
+    /// Posts a comment with `body` on the track with the id `track_id`, at `timestamp`
+    /// milliseconds into the track if given, and returns the created comment.
+    ///
+    /// Requires an access token, as the comment is posted by the authenticated user.
+    pub fn post_comment(
+        &self,
+        track_id: usize,
+        body: &str,
+        timestamp: Option<usize>,
+    ) -> Box<Future<Item = Comment, Error = Error>> {
+        if self.access_token.borrow().is_none() {
+            return Box::new(future::err(Error::MissingCredentials(
+                "access_token".to_owned(),
+            )));
+        }
+
+        let url = self.api_url(&format!("/tracks/{}/comments", track_id));
+        let mut headers = hyper::Headers::new();
+        headers.set(hyper::header::ContentType::form_url_encoded());
+
+        self.fetch_json(
+            hyper::Method::Post,
+            url.as_str(),
+            headers,
+            Some(comment_form(body, timestamp)),
+        )
+    }
+
+    /// Deletes the comment with the id `id`.
+    ///
+    /// Requires an access token for the author of the comment or the owner of the track.
+    pub fn delete_comment(&self, id: usize) -> Box<Future<Item = (), Error = Error>> {
+        if self.access_token.borrow().is_none() {
+            return Box::new(future::err(Error::MissingCredentials(
+                "access_token".to_owned(),
+            )));
+        }
+
+        let uri = match self.parse_url(self.api_url(&format!("/comments/{}", id))) {
+            Ok(uri) => uri,
+            Err(error) => return Box::new(future::err(error)),
+        };
+
+        let response = self
+            .fetch(hyper::Method::Delete, uri, hyper::Headers::new(), None)
+            .map(|_| ());
+
+        Box::new(response)
+    }
+
+    /// Parses a string and returns a url with the client_id query parameter set.
+    ///
+    /// Urls pointing at the API host are rewritten to the configured base url.
+    pub fn parse_url<S: AsRef<str>>(&self, url: S) -> Result<hyper::Uri> {
+        let mut url = self.rebase_url(Url::parse(url.as_ref())?);
+
+        // Cursors such as `next_href` already carry the client_id.
+        if !url.query_pairs().any(|(key, _)| key == "client_id") {
+            url.query_pairs_mut()
+                .append_pair("client_id", &self.client_id);
+        }
+
+        url.as_str().parse().map_err(|error| Error::UriError(error))
+    }
+}
+
+/// Passes successful and redirect responses through, and turns any other response into an
+/// `Error::Status` with the parsed error body.
+fn check_status(
+    url: String,
+    response: hyper::Response,
+) -> Box<Future<Item = hyper::Response, Error = Error>> {
+    let status = response.status();
+
+    if status.is_success() || status.is_redirection() {
+        return Box::new(future::ok(response));
+    }
+
+    let headers = response.headers().clone();
+    let retry_after = headers
+        .get::<hyper::header::RetryAfter>()
+        .map(|retry_after| match *retry_after {
+            hyper::header::RetryAfter::Delay(delay) => delay,
+            hyper::header::RetryAfter::DateTime(date) => SystemTime::from(date)
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::from_secs(0)),
+        });
+
+    let error = response
+        .body()
+        .concat2()
+        .map_err(|error| Error::HttpError(error))
+        .and_then(move |body| {
+            future::err(Error::Status(StatusError {
+                status: status,
+                url: redact_url(&url),
+                body: serde_json::from_slice(&body).ok(),
+                retry_after: retry_after,
+                headers: headers,
+            }))
+        });
+
+    Box::new(error)
+}
+
+/// Returns `url` without the query parameters that carry credentials, so that it can be shown in
+/// errors and logs.
+fn redact_url(url: &str) -> String {
+    const SECRETS: [&'static str; 4] =
+        ["client_id", "client_secret", "oauth_token", "access_token"];
+
+    let mut url = match Url::parse(url) {
+        Ok(url) => url,
+        // Drop the whole query if it can't be parsed.
+        Err(_) => return url.split('?').next().unwrap_or("").to_owned(),
+    };
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|&(ref key, _)| !SECRETS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    url.into_string()
+}
+
+/// Returns the body of `response` as a stream of byte chunks.
+fn body_bytes(response: hyper::Response) -> Box<Stream<Item = Bytes, Error = Error>> {
+    let bytes = response
+        .body()
+        .map(Bytes::from)
+        .map_err(|error| Error::HttpError(error));
+
+    Box::new(bytes)
+}
+
+/// Writes every chunk of `bytes` to `writer` and returns the number of bytes written.
+fn write_bytes<'a, S, W>(bytes: S, mut writer: W) -> Box<Future<Item = usize, Error = Error> + 'a>
+where
+    S: 'a + Stream<Item = Bytes, Error = Error>,
+    W: 'a + Write,
+{
+    let written = bytes.fold(0, move |acc, chunk| {
+        writer
+            .write_all(chunk.as_ref())
+            .map_err(|error| Error::Io(error))?;
+
+        Ok::<usize, Error>(acc + chunk.len())
+    });
+
+    Box::new(written)
+}
+
+/// Writes the body of `response` to `writer`, calling `on_progress` after every chunk, and returns
+/// the number of bytes written.
+fn write_body<'a, W, F>(
+    response: hyper::Response,
+    mut writer: W,
+    mut on_progress: F,
+) -> Box<Future<Item = usize, Error = Error> + 'a>
+where
+    W: 'a + Write,
+    F: 'a + FnMut(Progress),
+{
+    let total = response
+        .headers()
+        .get::<hyper::header::ContentLength>()
+        .map(|length| length.0);
+    let started = Instant::now();
+
+    let written = response
+        .body()
+        .map_err(|error| Error::HttpError(error))
+        .fold(0, move |acc, chunk| {
+            writer
+                .write_all(chunk.as_ref())
+                .map_err(|error| Error::Io(error))?;
+
+            let bytes = acc + chunk.len();
+
+            on_progress(Progress {
+                bytes: bytes as u64,
+                total: total,
+                elapsed: started.elapsed(),
+            });
+
+            Ok::<usize, Error>(bytes)
+        });
+
+    Box::new(written)
+}
+
+/// Writes the body of `response` to the asynchronous `writer`, flushes it and returns the number
+/// of bytes written.
+fn write_body_async<'a, W: 'a + AsyncWrite>(
+    response: hyper::Response,
+    writer: W,
+) -> Box<Future<Item = usize, Error = Error> + 'a> {
+    let written = response
+        .body()
+        .map_err(|error| Error::HttpError(error))
+        .fold((writer, 0), |(writer, acc), chunk| {
+            tokio_io::io::write_all(writer, chunk)
+                .map(move |(writer, chunk)| (writer, acc + chunk.len()))
+                .map_err(|error| Error::Io(error))
+        })
+        .and_then(|(writer, written)| {
+            tokio_io::io::flush(writer)
+                .map(move |_| written)
+                .map_err(|error| Error::Io(error))
+        });
+
+    Box::new(written)
+}
+
+/// Appends the body of a download `response` to `file`, which already holds the first `offset`
+/// bytes, and returns the size of the complete file.
+///
+/// A `206 Partial Content` response must continue exactly where the file ends, any other
+/// successful response replaces the contents of the file.
+fn write_range(
+    response: hyper::Response,
+    mut file: File,
+    offset: u64,
+) -> Box<Future<Item = u64, Error = Error>> {
+    let start = if response.status() == hyper::StatusCode::PartialContent {
+        match response.headers().get::<hyper::header::ContentRange>() {
+            Some(&hyper::header::ContentRange(hyper::header::ContentRangeSpec::Bytes {
+                range: Some((first, _)),
+                ..
+            })) if first == offset => offset,
+            _ => {
+                return Box::new(future::err(Error::UnexpectedRange(format!(
+                    "expected a content range starting at byte {}",
+                    offset
+                ))))
+            }
+        }
+    } else {
+        if let Err(error) = file.set_len(0) {
+            return Box::new(future::err(Error::Io(error)));
+        }
+
+        0
+    };
+
+    let content_length = response
+        .headers()
+        .get::<hyper::header::ContentLength>()
+        .map(|length| length.0);
+
+    let size = response
+        .body()
+        .map_err(|error| Error::HttpError(error))
+        .fold(0, move |written, chunk| {
+            file.write_all(chunk.as_ref())
+                .map(|_| written + chunk.len() as u64)
+                .map_err(|error| Error::Io(error))
+        })
+        .and_then(move |written| match content_length {
+            Some(expected) if expected != written => {
+                Err(Error::IncompleteDownload(expected, written))
+            }
+            _ => Ok(start + written),
+        });
+
+    Box::new(size)
+}
+
+/// Returns the complete length of the resource from the `Content-Range: bytes */<length>`
+/// header of a `416 Range Not Satisfiable` response.
+fn complete_length(headers: &hyper::Headers) -> Option<u64> {
+    match headers.get::<hyper::header::ContentRange>() {
+        Some(&hyper::header::ContentRange(hyper::header::ContentRangeSpec::Bytes {
+            range: None,
+            instance_length: Some(length),
+        })) => Some(length),
+        _ => None,
+    }
+}
+
+/// Returns where a redirect `response` to a request for `url` points, resolving a relative
+/// `Location` against `url` and moving it onto `base_url` if it points at the API host.
+///
+/// Returns `None` if the response isn't a redirect.
+fn redirect_location(base_url: &Url, url: &Url, response: &hyper::Response) -> Option<Result<Url>> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+
+    let location = response.headers().get::<hyper::header::Location>()?;
+
+    Some(
+        url.join(location)
+            .map(|location| rebase_url(base_url, location))
+            .map_err(|error| Error::ParseError(error)),
+    )
+}
+
+/// Returns the form-encoded request body for posting a comment.
+fn comment_form(body: &str, timestamp: Option<usize>) -> String {
+    let mut form = form_urlencoded::Serializer::new(String::new());
+    form.append_pair("comment[body]", body);
+
+    if let Some(timestamp) = timestamp {
+        form.append_pair("comment[timestamp]", &timestamp.to_string());
+    }
+
+    form.finish()
+}
+
+/// Moves `url` onto `base_url` if it points at the API host.
+fn rebase_url(base_url: &Url, url: Url) -> Url {
+    if url.host_str() != Some(super::API_HOST) || url.origin() == base_url.origin() {
+        return url;
+    }
+
+    let mut rebased = base_url.join(url.path()).unwrap();
+    rebased.set_query(url.query());
+    rebased
+}
+
+impl ClientBuilder {
+    /// Constructs a new `ClientBuilder` that targets the public SoundCloud API.
+    pub fn new(client_id: &str) -> ClientBuilder {
+        ClientBuilder {
+            client_id: client_id.to_owned(),
+            client_secret: None,
+            redirect_uri: None,
+            access_token: None,
+            scheme: "https".to_owned(),
+            host: super::API_HOST.to_owned(),
+            port: None,
+            retry_policy: RetryPolicy::none(),
+            rate_limiter: None,
+            max_redirects: 10,
+        }
+    }
+
+    /// Sets the client secret, which is required to request access tokens.
+    pub fn client_secret(&mut self, client_secret: &str) -> &mut ClientBuilder {
+        self.client_secret = Some(client_secret.to_owned());
+        self
+    }
+
+    /// Sets the redirect uri registered for the application, which is required for the
+    /// authorization code flow.
+    pub fn redirect_uri(&mut self, redirect_uri: &str) -> &mut ClientBuilder {
+        self.redirect_uri = Some(redirect_uri.to_owned());
+        self
+    }
+
+    /// Sets an access token obtained earlier, e.g. one that was persisted between runs.
+    pub fn access_token(&mut self, access_token: AccessToken) -> &mut ClientBuilder {
+        self.access_token = Some(access_token);
+        self
+    }
+
+    /// Sets the scheme used for API requests, e.g. `http` for a local mock server.
+    pub fn scheme(&mut self, scheme: &str) -> &mut ClientBuilder {
+        self.scheme = scheme.to_owned();
+        self
+    }
+
+    /// Sets the host that API requests are sent to.
+    pub fn host(&mut self, host: &str) -> &mut ClientBuilder {
+        self.host = host.to_owned();
+        self
+    }
+
+    /// Sets the port that API requests are sent to, or the scheme's default port if `None`.
+    pub fn port(&mut self, port: Option<u16>) -> &mut ClientBuilder {
+        self.port = port;
+        self
+    }
+
+    /// Sets the policy for retrying requests that failed with a transient error. By default
+    /// requests aren't retried.
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut ClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the rate limiter that API requests are queued through. By default requests are sent
+    /// immediately.
+    ///
+    /// The limiter can be cloned and passed to several clients to share its quotas between them.
+    pub fn rate_limiter(&mut self, rate_limiter: RateLimiter) -> &mut ClientBuilder {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Sets the maximum number of redirects followed for a single request. Defaults to 10.
+    pub fn max_redirects(&mut self, max_redirects: usize) -> &mut ClientBuilder {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Constructs the `Client`, returning an error if the scheme, host and port don't form a
+    /// valid url.
+    pub fn build(&self, handle: &tokio_core::reactor::Handle) -> Result<Client> {
+        let base_url = match self.port {
+            Some(port) => Url::parse(&format!("{}://{}:{}", self.scheme, self.host, port))?,
+            None => Url::parse(&format!("{}://{}", self.scheme, self.host))?,
+        };
+
+        let client = hyper::Client::configure()
+            .connector(hyper_tls::HttpsConnector::new(4, &handle).unwrap())
+            .build(&handle);
+
+        Ok(Client {
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            access_token: RefCell::new(self.access_token.clone()),
+            base_url: base_url,
+            retry_policy: self.retry_policy.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            max_redirects: self.max_redirects,
+            handle: handle.clone(),
+            http_client: client,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures;
+    use url::Url;
+
+    fn client(handle: &tokio_core::reactor::Handle) -> Client {
+        Client::new(env!("SOUNDCLOUD_CLIENT_ID"), handle)
+    }
+
+    #[test]
+    fn test_comment_form() {
+        assert_eq!(
+            comment_form("nice drop & all", Some(61000)),
+            "comment%5Bbody%5D=nice+drop+%26+all&comment%5Btimestamp%5D=61000"
+        );
+        assert_eq!(comment_form("hi", None), "comment%5Bbody%5D=hi");
+    }
+
+    #[test]
+    fn test_comment_requires_access_token() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = Client::new("abc", &core.handle());
+
+        match client.post_comment(1, "hi", None).wait() {
+            Err(Error::MissingCredentials(ref name)) => assert_eq!(name, "access_token"),
+            _ => panic!("expected missing credentials"),
+        }
+
+        match client.delete_comment(1).wait() {
+            Err(Error::MissingCredentials(ref name)) => assert_eq!(name, "access_token"),
+            _ => panic!("expected missing credentials"),
+        }
+    }
+
+    /// A comment as returned by `/comments/{id}`, with the mini representation of its user.
+    const COMMENT: &'static str = r#"{
+        "kind": "comment", "id": 703169, "created_at": "2011/04/07 10:12:01 +0000",
+        "user_id": 3699101, "track_id": 13158665, "timestamp": null, "body": "so good",
+        "uri": "https://api.soundcloud.com/comments/703169",
+        "user": {"id": 3699101, "permalink": "alex-stevenson", "username": "Alex Stevenson",
+                 "uri": "https://api.soundcloud.com/users/3699101",
+                 "permalink_url": "https://soundcloud.com/alex-stevenson"}
+    }"#;
+
+    #[test]
+    fn test_deserialize_comment() {
+        let comment: Comment = serde_json::from_str(COMMENT).unwrap();
+
+        assert_eq!(comment.id, 703169);
+        assert_eq!(comment.created_at.to_rfc3339(), "2011-04-07T10:12:01+00:00");
+        assert_eq!(comment.body, "so good");
+        assert_eq!(comment.timestamp, None);
+        assert_eq!(comment.track_id, 13158665);
+        assert_eq!(comment.user.username, "Alex Stevenson");
+    }
+
+    /// Returns the resource in `json` with its `kind` set to `kind`.
+    fn with_kind(json: &str, kind: &str) -> Value {
+        let mut resource: Value = serde_json::from_str(json).unwrap();
+        resource["kind"] = Value::from(kind);
+        resource
+    }
+
+    #[test]
+    fn test_deserialize_resource() {
+        let user = r#"{"id": 3699101, "permalink": "alex-stevenson", "username": "Alex Stevenson",
+                       "uri": "https://api.soundcloud.com/users/3699101",
+                       "permalink_url": "https://soundcloud.com/alex-stevenson"}"#;
+        let playlist = format!(
+            r#"{{"id": 405726, "created_at": "2011/04/08 09:21:36 +0000", "user_id": 3699101,
+                 "user": {}, "title": "Field recordings", "permalink": "field-recordings",
+                 "permalink_url": "https://soundcloud.com/alex-stevenson/sets/field-recordings",
+                 "uri": "https://api.soundcloud.com/playlists/405726", "sharing": "public",
+                 "duration": 18109, "tracks": []}}"#,
+            user
+        );
+
+        match serde_json::from_value(with_kind(fixtures::TRACK, "track")).unwrap() {
+            Resource::Track(track) => assert_eq!(track.id, 13158665),
+            resource => panic!("expected a track, got {:?}", resource),
+        }
+
+        match serde_json::from_value(with_kind(user, "user")).unwrap() {
+            Resource::User(user) => assert_eq!(user.id, 3699101),
+            resource => panic!("expected a user, got {:?}", resource),
+        }
+
+        match serde_json::from_value(with_kind(&playlist, "playlist")).unwrap() {
+            Resource::Playlist(playlist) => assert_eq!(playlist.id, 405726),
+            resource => panic!("expected a playlist, got {:?}", resource),
+        }
+
+        match serde_json::from_str(COMMENT).unwrap() {
+            Resource::Comment(comment) => assert_eq!(comment.id, 703169),
+            resource => panic!("expected a comment, got {:?}", resource),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_other_resource() {
+        let app = r#"{"kind":"app","id":1,"name":"Cool App"}"#;
+
+        match serde_json::from_str(app).unwrap() {
+            Resource::Other(ref value) => assert_eq!(value["name"], "Cool App"),
+            resource => panic!("expected another resource, got {:?}", resource),
+        }
+
+        let resource: Resource = serde_json::from_str(r#"{"id":1}"#).unwrap();
+        assert!(match resource {
+            Resource::Other(_) => true,
+            _ => false,
+        });
+
+        // A known kind that doesn't match its schema is still an error.
+        assert!(serde_json::from_str::<Resource>(r#"{"kind":"track","id":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_serialize_resource() {
+        let track = serde_json::to_value(Resource::Track(fixtures::track())).unwrap();
+        assert_eq!(track["kind"], "track");
+        assert_eq!(track["id"], 13158665);
+
+        let app: Value = serde_json::from_str(r#"{"kind":"app","id":1}"#).unwrap();
+        assert_eq!(
+            serde_json::to_value(Resource::Other(app.clone())).unwrap(),
+            app
+        );
+    }
+
+    #[test]
+    fn test_get_comment() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+        let port = stub_server(|path, _| match path {
+            "/comments/703169" => ("200 OK", String::new(), COMMENT.to_owned()),
+            _ => ("404 Not Found", String::new(), String::new()),
+        });
+        let client = Client::builder("abc")
+            .scheme("http")
+            .host("127.0.0.1")
+            .port(Some(port))
+            .build(&core.handle())
+            .unwrap();
+
+        let comment = core.run(client.comment(703169)).unwrap();
+        assert_eq!(comment.id, 703169);
+        assert_eq!(comment.user_id, 3699101);
+
+        match core.run(client.comment(1)) {
+            Err(Error::Status(ref error)) => assert_eq!(error.status, hyper::NotFound),
+            _ => panic!("expected a status error"),
+        }
+    }
+
+    #[test]
+    fn test_get_track_comments() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let comments = core
+            .run(client.track_comments(262681089, Some(5)).collect())
+            .unwrap();
+
+        assert!(comments.iter().all(|comment| comment.track_id == 262681089));
+    }
+
+    #[test]
+    fn test_base_url_override() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = Client::builder("abc")
+            .scheme("http")
+            .host("localhost")
+            .port(Some(8080))
+            .build(&core.handle())
+            .unwrap();
+
+        assert_eq!(
+            client.api_url("/tracks/1").as_str(),
+            "http://localhost:8080/tracks/1"
+        );
+        assert_eq!(
+            client
+                .parse_url("https://api.soundcloud.com/tracks/1/download")
+                .unwrap()
+                .to_string(),
+            "http://localhost:8080/tracks/1/download?client_id=abc"
+        );
+        assert_eq!(
+            client
+                .parse_url("https://cf-media.sndcdn.com/abc.mp3")
+                .unwrap()
+                .to_string(),
+            "https://cf-media.sndcdn.com/abc.mp3?client_id=abc"
+        );
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = Client::builder("abc")
+            .redirect_uri("http://localhost/callback")
+            .build(&core.handle())
+            .unwrap();
+
+        assert_eq!(
+            client.authorize_url(Some("non-expiring"), None).unwrap().as_str(),
+            "https://soundcloud.com/connect?client_id=abc&redirect_uri=http%3A%2F%2Flocalhost%2Fcallback&response_type=code&scope=non-expiring"
+        );
+    }
+
+    #[test]
+    fn test_token_form() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = Client::builder("abc")
+            .client_secret("s3cr3t")
+            .redirect_uri("http://localhost/callback")
+            .build(&core.handle())
+            .unwrap();
+
+        assert_eq!(
+            client
+                .token_form(&Grant::AuthorizationCode("c0de".to_owned()))
+                .unwrap(),
+            "client_id=abc&client_secret=s3cr3t&grant_type=authorization_code\
+             &redirect_uri=http%3A%2F%2Flocalhost%2Fcallback&code=c0de"
+        );
+        assert_eq!(
+            client
+                .token_form(&Grant::RefreshToken("1-2".to_owned()))
+                .unwrap(),
+            "client_id=abc&client_secret=s3cr3t&grant_type=refresh_token&refresh_token=1-2"
+        );
+        assert_eq!(
+            client.token_form(&Grant::ClientCredentials).unwrap(),
+            "client_id=abc&client_secret=s3cr3t&grant_type=client_credentials"
+        );
+    }
+
+    #[test]
+    fn test_token_form_missing_credentials() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = Client::builder("abc")
+            .client_secret("s3cr3t")
+            .build(&core.handle())
+            .unwrap();
+
+        match client.token_form(&Grant::AuthorizationCode("c0de".to_owned())) {
+            Err(Error::MissingCredentials(ref name)) => assert_eq!(name, "redirect_uri"),
+            _ => panic!("expected missing redirect_uri"),
+        }
+
+        let client = Client::new("abc", &core.handle());
+
+        match client.token_form(&Grant::ClientCredentials) {
+            Err(Error::MissingCredentials(ref name)) => assert_eq!(name, "client_secret"),
+            _ => panic!("expected missing client_secret"),
+        }
+    }
+
+    #[test]
+    fn test_check_status() {
+        let response = hyper::Response::new()
+            .with_status(hyper::StatusCode::NotFound)
+            .with_body(r#"{"errors":[{"error_message":"404 - Not Found"}]}"#);
+
+        let error = check_status(
+            "https://api.soundcloud.com/tracks/1?client_id=abc".to_owned(),
+            response,
+        )
+        .wait()
+        .unwrap_err();
+
+        assert!(error.is_not_found());
+        assert!(!error.is_unauthorized());
+        assert_eq!(
+            error.to_string(),
+            "HTTP error 404 Not Found for https://api.soundcloud.com/tracks/1: 404 - Not Found"
+        );
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("https://api.soundcloud.com/tracks?q=a+b&client_id=abc&oauth_token=1-2"),
+            "https://api.soundcloud.com/tracks?q=a+b"
+        );
+        assert_eq!(
+            redact_url("https://api.soundcloud.com/me?client_id=abc"),
+            "https://api.soundcloud.com/me"
+        );
+        assert_eq!(redact_url("not a url?client_id=abc"), "not a url");
+    }
+
+    #[test]
+    fn test_redirect_location() {
+        let base_url = Url::parse("http://localhost:8080").unwrap();
+        let url = Url::parse("http://localhost:8080/tracks/1/download").unwrap();
+        let redirect = |location: &str| {
+            hyper::Response::new()
+                .with_status(hyper::StatusCode::Found)
+                .with_header(hyper::header::Location::new(location.to_owned()))
+        };
+
+        let location = redirect_location(&base_url, &url, &redirect("/tracks/2"));
+        assert_eq!(
+            location.unwrap().unwrap().as_str(),
+            "http://localhost:8080/tracks/2"
+        );
+
+        let location = redirect_location(&base_url, &url, &redirect("stream"));
+        assert_eq!(
+            location.unwrap().unwrap().as_str(),
+            "http://localhost:8080/tracks/1/stream"
+        );
+
+        let location = redirect_location(
+            &base_url,
+            &url,
+            &redirect("https://api.soundcloud.com/tracks/3?secret_token=s-1"),
+        );
+        assert_eq!(
+            location.unwrap().unwrap().as_str(),
+            "http://localhost:8080/tracks/3?secret_token=s-1"
+        );
+
+        let location = redirect_location(&base_url, &url, &redirect("http://[::1"));
+        assert!(location.unwrap().is_err());
+
+        let response = hyper::Response::new().with_header(hyper::header::Location::new("/"));
+        assert!(redirect_location(&base_url, &url, &response).is_none());
+    }
+
+    /// Serves HTTP on a local port and returns the port. Every request is answered with the
+    /// status line, headers and body that `respond` returns for its path and raw request.
+    fn stub_server<F>(respond: F) -> u16
+    where
+        F: Fn(&str, &str) -> (&'static str, String, String) + Send + 'static,
+    {
+        use std::io::Read;
+        use std::net::TcpListener;
+        use std::thread;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => return,
+                };
+
+                let mut buffer = [0; 4096];
+                let len = stream.read(&mut buffer).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..len]).into_owned();
+                let target = request.split_whitespace().nth(1).unwrap_or("/");
+                let path = target.split('?').next().unwrap_or("/");
+                let (status, headers, body) = respond(path, &request);
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        port
+    }
+
+    /// Serves redirects on a local port and returns the port. `/r/<n>` redirects to `/r/<n + 1>`,
+    /// `/a` redirects to `/b` and `/b` back to `/a`.
+    fn redirect_server() -> u16 {
+        stub_server(|path, _| {
+            let location = if path.starts_with("/r/") {
+                format!("/r/{}", path[3..].parse::<u32>().unwrap_or(0) + 1)
+            } else if path == "/a" {
+                "/b".to_owned()
+            } else {
+                "/a".to_owned()
+            };
+
+            (
+                "302 Found",
+                format!("Location: {}\r\n", location),
+                String::new(),
+            )
+        })
+    }
+
+    #[cfg(feature = "id3")]
+    #[test]
+    fn test_cover_artwork_fallback() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+        let port = stub_server(|path, _| match path {
+            "/artworks-1-a-large.jpg" => ("200 OK", String::new(), "large".to_owned()),
+            "/artworks-2-b-t500x500.jpg" => ("200 OK", String::new(), "t500x500".to_owned()),
+            _ => ("404 Not Found", String::new(), String::new()),
+        });
+        let client = Client::new("abc", &core.handle());
+        let url = |name: &str| ArtworkUrl::new(&format!("http://127.0.0.1:{}/{}", port, name));
+
+        let artwork = core
+            .run(client.cover_artwork(&url("artworks-1-a-large.jpg")))
+            .unwrap();
+        assert_eq!(artwork, Some(Bytes::from("large")));
+
+        let artwork = core
+            .run(client.cover_artwork(&url("artworks-2-b-large.jpg")))
+            .unwrap();
+        assert_eq!(artwork, Some(Bytes::from("t500x500")));
+
+        let artwork = core
+            .run(client.cover_artwork(&url("artworks-3-c-large.jpg")))
+            .unwrap();
+        assert_eq!(artwork, None);
+    }
+
+    #[test]
+    fn test_get_redirected_limits() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+        let port = redirect_server();
+        let client = Client::builder("abc")
+            .scheme("http")
+            .host("127.0.0.1")
+            .port(Some(port))
+            .max_redirects(3)
+            .build(&core.handle())
+            .unwrap();
+
+        let uri = client.parse_url(client.api_url("/r/0")).unwrap();
+        match core.run(client.get_redirected(uri, hyper::Headers::new())) {
+            Err(Error::TooManyRedirects(3)) => (),
+            result => panic!("expected too many redirects, got {:?}", result.map(|_| ())),
+        }
+
+        let uri = client.parse_url(client.api_url("/a")).unwrap();
+        match core.run(client.get_redirected(uri, hyper::Headers::new())) {
+            // The first url carries a client_id, so the loop is detected on the way back to `/b`.
+            Err(Error::RedirectLoop(ref url)) => {
+                assert_eq!(url, &format!("http://127.0.0.1:{}/b", port))
+            }
+            result => panic!("expected a redirect loop, got {:?}", result.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_body_bytes() {
+        let response = hyper::Response::new().with_body("hello world");
+        let chunks = body_bytes(response).collect().wait().unwrap();
+
+        assert_eq!(chunks, vec![Bytes::from("hello world")]);
+    }
+
+    #[test]
+    fn test_write_body_progress() {
+        let response = hyper::Response::new()
+            .with_header(hyper::header::ContentLength(11))
+            .with_body("hello world");
+
+        let mut events = vec![];
+        let written = write_body(response, vec![], |progress| events.push(progress))
+            .wait()
+            .unwrap();
+
+        assert_eq!(written, 11);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].bytes, 11);
+        assert_eq!(events[0].total, Some(11));
+    }
+
+    #[test]
+    fn test_write_body_short_writes() {
+        use std::io;
+
+        // Accepts at most three bytes per call, like a pipe with a small buffer.
+        struct ShortWriter(Vec<u8>);
+
+        impl Write for ShortWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let len = buf.len().min(3);
+                self.0.extend_from_slice(&buf[..len]);
+                Ok(len)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = ShortWriter(vec![]);
+        let response = hyper::Response::new().with_body("hello world");
+        let written = write_body(response, &mut writer, |_| ()).wait().unwrap();
+
+        assert_eq!(written, 11);
+        assert_eq!(writer.0, b"hello world");
+    }
+
+    #[test]
+    fn test_write_body_io_error() {
+        use std::io;
+
+        struct FullDisk;
+
+        impl Write for FullDisk {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let response = hyper::Response::new().with_body("hello world");
+
+        match write_body(response, FullDisk, |_| ()).wait() {
+            Err(Error::Io(_)) => (),
+            result => panic!("expected an io error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_write_body_async() {
+        use std::io::Cursor;
+
+        let mut writer = Cursor::new(vec![]);
+        let response = hyper::Response::new().with_body("hello world");
+        let written = write_body_async(response, &mut writer).wait().unwrap();
+
+        assert_eq!(written, 11);
+        assert_eq!(writer.into_inner(), b"hello world");
+    }
+
+    #[test]
+    fn test_write_range() {
+        use std::fs;
+        use std::io::Read;
+
+        let path = ::std::env::temp_dir().join(format!(
+            "soundcloud-test-write-range-{}",
+            ::std::process::id()
+        ));
+        fs::write(&path, "hello ").unwrap();
+
+        let file = OpenOptions::new().append(true).open(&path).unwrap();
+        let response = hyper::Response::new()
+            .with_status(hyper::StatusCode::PartialContent)
+            .with_header(hyper::header::ContentRange(
+                hyper::header::ContentRangeSpec::Bytes {
+                    range: Some((6, 10)),
+                    instance_length: Some(11),
+                },
+            ))
+            .with_header(hyper::header::ContentLength(5))
+            .with_body("world");
+
+        assert_eq!(write_range(response, file, 6).wait().unwrap(), 11);
+
+        let mut contents = String::new();
+        File::open(&path)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "hello world");
+
+        let file = OpenOptions::new().append(true).open(&path).unwrap();
+        let response = hyper::Response::new()
+            .with_status(hyper::StatusCode::PartialContent)
+            .with_header(hyper::header::ContentRange(
+                hyper::header::ContentRangeSpec::Bytes {
+                    range: Some((0, 10)),
+                    instance_length: Some(11),
+                },
+            ))
+            .with_body("hello world");
+
+        match write_range(response, file, 11).wait() {
+            Err(Error::UnexpectedRange(_)) => (),
+            result => panic!("expected an unexpected range error, got {:?}", result),
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_to_file_mismatched_length() {
+        use std::fs;
+
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+        let port = stub_server(|_, request| {
+            if request.contains("Range: bytes=") {
+                (
+                    "416 Range Not Satisfiable",
+                    "Content-Range: bytes */5\r\n".to_owned(),
+                    String::new(),
+                )
+            } else {
+                ("200 OK", String::new(), "fresh".to_owned())
+            }
+        });
+        let client = Client::new("abc", &core.handle());
+        let uri: hyper::Uri = format!("http://127.0.0.1:{}/track", port).parse().unwrap();
+        let path = ::std::env::temp_dir().join(format!(
+            "soundcloud-test-resume-to-file-{}",
+            ::std::process::id()
+        ));
+
+        // A file with the length of the track is left as it is.
+        fs::write(&path, "hello").unwrap();
+        assert_eq!(
+            core.run(client.resume_to_file(uri.clone(), &path)).unwrap(),
+            5
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+
+        // A file that is longer than the track is downloaded again.
+        fs::write(&path, "hello world").unwrap();
+        assert_eq!(core.run(client.resume_to_file(uri, &path)).unwrap(), 5);
+        assert_eq!(fs::read(&path).unwrap(), b"fresh");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_retry_transient_errors() {
+        use std::cell::Cell;
+
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+        let mut policy = RetryPolicy::new(3);
+        policy.base_delay = Duration::from_millis(1);
+
+        let attempts = Cell::new(0);
+        let work = retry::retry(&core.handle(), &policy, || {
+            attempts.set(attempts.get() + 1);
+
+            let response =
+                hyper::Response::new().with_status(hyper::StatusCode::ServiceUnavailable);
+            check_status("https://api.soundcloud.com/tracks".to_owned(), response)
+        });
+
+        assert!(core.run(work).unwrap_err().is_server_error());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn test_resolve_track() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let work = client(&core.handle()).resolve("https://soundcloud.com/isqa/tree-eater-1");
+
+        let result = core.run(work);
+
+        assert_eq!(
             result.unwrap(),
             Url::parse(&format!(
                 "https://api.soundcloud.com/tracks/262976655?client_id={}",
@@ -350,6 +2373,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_track_resource() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let client = client(&core.handle());
+        let work = client.resolve_resource("https://soundcloud.com/isqa/tree-eater-1");
+
+        match core.run(work).unwrap() {
+            Resource::Track(track) => assert_eq!(track.id, 262976655),
+            resource => panic!("expected a track, got {:?}", resource),
+        }
+    }
+
     #[test]
     fn test_get_tracks() {
         let mut core = tokio_core::reactor::Core::new().unwrap();
@@ -364,6 +2400,69 @@ mod tests {
         assert!(result.unwrap().is_some());
     }
 
+    #[test]
+    fn test_paginate_tracks() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let client = client(&core.handle());
+        let work = client
+            .tracks()
+            .query(Some("noisia"))
+            .limit(Some(10))
+            .paginate(Some(25))
+            .collect();
+
+        let tracks = core.run(work).unwrap();
+
+        assert_eq!(tracks.len(), 25);
+    }
+
+    #[test]
+    fn test_get_user() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let work = client(&core.handle()).user(3207).get();
+
+        let user = core.run(work).unwrap();
+
+        assert_eq!(user.id, 3207);
+    }
+
+    #[test]
+    fn test_get_user_tracks() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let client = client(&core.handle());
+        let work = client.user(3207).tracks(Some(5)).collect();
+
+        let tracks = core.run(work).unwrap();
+
+        assert!(tracks.iter().all(|track| track.user_id == 3207));
+    }
+
+    #[test]
+    fn test_get_users() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let work = client(&core.handle()).users().query(Some("noisia")).get();
+
+        let result = core.run(work);
+
+        assert!(result.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_get_playlist() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let work = client(&core.handle()).playlist(405726).get();
+
+        let playlist = core.run(work).unwrap();
+
+        assert_eq!(playlist.id, 405726);
+        assert!(!playlist.tracks.is_empty());
+    }
+
     #[test]
     fn test_get_track() {
         let mut core = tokio_core::reactor::Core::new().unwrap();
@@ -419,4 +2518,18 @@ mod tests {
         buffer.flush();
         assert!(buffer.get_ref().len() > 0);
     }
+
+    #[test]
+    fn test_stream_bytes() {
+        let mut core = tokio_core::reactor::Core::new().unwrap();
+
+        let client = client(&core.handle());
+        let work = client.tracks().id(262681089).get().and_then(|track| {
+            client
+                .stream_bytes(&track)
+                .fold(0, |len, chunk| Ok::<usize, Error>(len + chunk.len()))
+        });
+
+        assert!(core.run(work).unwrap() > 0);
+    }
 }
@@ -0,0 +1,151 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! (De)serialization of timestamps in the format used by the API, for use with
+//! `#[serde(with = "date")]`, or `#[serde(default, with = "date::option")]` for optional ones.
+
+use std::result;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Format of timestamps in the API, e.g. `2011/04/06 15:37:43 +0000`.
+pub const FORMAT: &'static str = "%Y/%m/%d %H:%M:%S %z";
+
+/// Serializes `date` in the API format.
+pub fn serialize<S: Serializer>(
+    date: &DateTime<Utc>,
+    serializer: S,
+) -> result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&date.format(FORMAT).to_string())
+}
+
+/// Deserializes a timestamp in the API format.
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> result::Result<DateTime<Utc>, D::Error> {
+    let date = String::deserialize(deserializer)?;
+
+    DateTime::parse_from_str(&date, FORMAT)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(de::Error::custom)
+}
+
+/// (De)serialization of optional timestamps, where `null` is `None`.
+pub mod option {
+    use std::result;
+
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes `date` in the API format, or as `null` if it's `None`.
+    pub fn serialize<S: Serializer>(
+        date: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> result::Result<S::Ok, S::Error> {
+        match *date {
+            Some(ref date) => super::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes a timestamp in the API format, or `null`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> result::Result<Option<DateTime<Utc>>, D::Error> {
+        #[derive(Deserialize)]
+        struct Date(#[serde(with = "super")] DateTime<Utc>);
+
+        let date = Option::<Date>::deserialize(deserializer)?;
+
+        Ok(date.map(|Date(date)| date))
+    }
+}
+
+/// Returns the date made up of a separate `year`, `month` and `day`, if all of them are set and
+/// form a valid date.
+pub fn from_parts(year: Option<u64>, month: Option<u64>, day: Option<u64>) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year? as i32, month? as u32, day? as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+    use serde_json;
+
+    #[derive(Serialize, Deserialize)]
+    struct Resource {
+        #[serde(with = "super")]
+        created_at: DateTime<Utc>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let json = r#"{"created_at":"2011/04/06 15:37:43 +0000"}"#;
+        let resource: Resource = serde_json::from_str(json).unwrap();
+
+        assert_eq!(resource.created_at.year(), 2011);
+        assert_eq!(resource.created_at.month(), 4);
+        assert_eq!(resource.created_at.day(), 6);
+        assert_eq!(resource.created_at.hour(), 15);
+        assert_eq!(serde_json::to_string(&resource).unwrap(), json);
+    }
+
+    #[test]
+    fn test_offset() {
+        let resource: Resource =
+            serde_json::from_str(r#"{"created_at":"2011/04/06 15:37:43 +0200"}"#).unwrap();
+
+        assert_eq!(resource.created_at.hour(), 13);
+    }
+
+    #[test]
+    fn test_invalid_timestamp() {
+        assert!(
+            serde_json::from_str::<Resource>(r#"{"created_at":"2011-04-06T15:37:43Z"}"#).is_err()
+        );
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct OptionalResource {
+        #[serde(default, with = "super::option")]
+        created_at: Option<DateTime<Utc>>,
+    }
+
+    #[test]
+    fn test_option() {
+        let json = r#"{"created_at":"2011/04/06 15:37:43 +0000"}"#;
+        let resource: OptionalResource = serde_json::from_str(json).unwrap();
+
+        assert_eq!(resource.created_at.map(|date| date.day()), Some(6));
+        assert_eq!(serde_json::to_string(&resource).unwrap(), json);
+
+        let resource: OptionalResource = serde_json::from_str(r#"{"created_at":null}"#).unwrap();
+        assert!(resource.created_at.is_none());
+        assert_eq!(
+            serde_json::to_string(&resource).unwrap(),
+            r#"{"created_at":null}"#
+        );
+
+        let resource: OptionalResource = serde_json::from_str("{}").unwrap();
+        assert!(resource.created_at.is_none());
+    }
+
+    #[test]
+    fn test_from_parts() {
+        assert_eq!(
+            from_parts(Some(2016), Some(2), Some(29)),
+            NaiveDate::from_ymd_opt(2016, 2, 29)
+        );
+        assert_eq!(from_parts(Some(2016), None, Some(29)), None);
+        assert_eq!(from_parts(Some(2015), Some(2), Some(29)), None);
+    }
+}
@@ -11,24 +11,89 @@ use std::error;
 use std::fmt;
 use std::io;
 use std::result;
+use std::time::Duration;
 
 use hyper;
+#[cfg(feature = "id3")]
+use id3;
+use png;
 use serde_json;
 use url;
 
 pub type Result<T> = result::Result<T, Error>;
 
+/// Single message in an error response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorMessage {
+    /// Human-readable description of the error.
+    pub error_message: String,
+}
+
+/// Body of an error response from the API.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorBody {
+    /// List of error messages.
+    #[serde(default)]
+    pub errors: Vec<ErrorMessage>,
+}
+
+/// Non-successful response from the API.
+#[derive(Debug)]
+pub struct StatusError {
+    /// HTTP status code of the response.
+    pub status: hyper::StatusCode,
+    /// URL of the request, without the parameters that carry credentials.
+    pub url: String,
+    /// The parsed error body, if the response had one.
+    pub body: Option<ErrorBody>,
+    /// How long to wait before retrying, if the response had a `Retry-After` header.
+    pub retry_after: Option<Duration>,
+    /// Headers of the response.
+    pub headers: hyper::Headers,
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HTTP error {} for {}", self.status, self.url)?;
+
+        if let Some(ref body) = self.body {
+            let messages: Vec<&str> = body
+                .errors
+                .iter()
+                .map(|error| error.error_message.as_ref())
+                .collect();
+
+            if !messages.is_empty() {
+                write!(f, ": {}", messages.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     ApiError(String),
     ParseError(url::ParseError),
     JsonError(serde_json::Error),
     HttpError(hyper::Error),
+    Status(StatusError),
     InvalidFilter(String),
+    MissingCredentials(String),
     Io(io::Error),
     UriError(hyper::error::UriError),
     TrackNotDownloadable,
     TrackNotStreamable,
+    UnexpectedRange(String),
+    IncompleteDownload(u64, u64),
+    TooManyRedirects(usize),
+    RedirectLoop(String),
+    InvalidPlaylist(String),
+    InvalidTemplate(String),
+    Png(png::DecodingError),
+    #[cfg(feature = "id3")]
+    Tag(id3::Error),
 }
 
 impl fmt::Display for Error {
@@ -36,13 +101,28 @@ impl fmt::Display for Error {
         match *self {
             Error::JsonError(ref error) => write!(f, "JSON error: {}", error),
             Error::HttpError(ref error) => write!(f, "HTTP error: {}", error),
+            Error::Status(ref error) => write!(f, "{}", error),
             Error::ApiError(ref error) => write!(f, "SoundCloud error: {}", error),
             Error::ParseError(ref error) => write!(f, "Parse error: {}", error),
             Error::Io(ref error) => write!(f, "IO error: {}", error),
             Error::UriError(ref error) => write!(f, "URI error: {}", error),
             Error::InvalidFilter(_) => write!(f, "Invalid filter"),
+            Error::MissingCredentials(ref name) => write!(f, "Missing credentials: {}", name),
             Error::TrackNotStreamable => write!(f, "The track is not available for streaming"),
             Error::TrackNotDownloadable => write!(f, "The track is not available for download"),
+            Error::UnexpectedRange(ref error) => write!(f, "Unexpected range: {}", error),
+            Error::TooManyRedirects(max) => write!(f, "More than {} redirects", max),
+            Error::RedirectLoop(ref url) => write!(f, "Redirect loop at {}", url),
+            Error::InvalidPlaylist(ref error) => write!(f, "Invalid playlist: {}", error),
+            Error::InvalidTemplate(ref error) => write!(f, "Invalid template: {}", error),
+            Error::Png(ref error) => write!(f, "PNG error: {}", error),
+            #[cfg(feature = "id3")]
+            Error::Tag(ref error) => write!(f, "Tag error: {}", error),
+            Error::IncompleteDownload(expected, received) => write!(
+                f,
+                "Incomplete download: expected {} bytes, received {}",
+                expected, received
+            ),
         }
     }
 }
@@ -51,11 +131,22 @@ impl error::Error for Error {
     fn description(&self) -> &str {
         match *self {
             Error::InvalidFilter(_) => "invalid filter",
+            Error::MissingCredentials(_) => "missing credentials",
             Error::ApiError(_) => "api error",
             Error::HttpError(ref error) => error.description(),
+            Error::Status(_) => "unsuccessful response",
             Error::JsonError(ref error) => error.description(),
             Error::TrackNotStreamable => "track is not streamable",
             Error::TrackNotDownloadable => "track is not downloadable",
+            Error::UnexpectedRange(_) => "unexpected range",
+            Error::IncompleteDownload(..) => "incomplete download",
+            Error::TooManyRedirects(_) => "too many redirects",
+            Error::RedirectLoop(_) => "redirect loop",
+            Error::InvalidPlaylist(_) => "invalid playlist",
+            Error::InvalidTemplate(_) => "invalid template",
+            Error::Png(ref error) => error.description(),
+            #[cfg(feature = "id3")]
+            Error::Tag(_) => "tag error",
             Error::ParseError(ref error) => error.description(),
             Error::Io(ref error) => error.description(),
             Error::UriError(ref error) => error.description(),
@@ -67,6 +158,65 @@ impl error::Error for Error {
             Error::JsonError(ref error) => Some(error),
             Error::HttpError(ref error) => Some(error),
             Error::Io(ref error) => Some(error),
+            Error::Png(ref error) => Some(error),
+            #[cfg(feature = "id3")]
+            Error::Tag(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the HTTP status code if the error was caused by a non-successful response.
+    pub fn status(&self) -> Option<hyper::StatusCode> {
+        match *self {
+            Error::Status(ref error) => Some(error.status),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the requested resource doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(hyper::StatusCode::NotFound)
+    }
+
+    /// Returns true if the request lacked valid credentials.
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(hyper::StatusCode::Unauthorized)
+    }
+
+    /// Returns true if the credentials don't grant access to the requested resource.
+    pub fn is_forbidden(&self) -> bool {
+        self.status() == Some(hyper::StatusCode::Forbidden)
+    }
+
+    /// Returns true if the request was rejected because too many requests were made.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(hyper::StatusCode::TooManyRequests)
+    }
+
+    /// Returns true if the API failed to handle the request.
+    pub fn is_server_error(&self) -> bool {
+        self.status()
+            .map_or(false, |status| status.is_server_error())
+    }
+
+    /// Returns true if the error is likely to go away if the request is sent again, such as a
+    /// connection reset, a server error or a rate limited response.
+    pub fn is_transient(&self) -> bool {
+        match *self {
+            Error::HttpError(hyper::Error::Io(_)) | Error::HttpError(hyper::Error::Incomplete) => {
+                true
+            }
+            Error::Status(_) => self.is_server_error() || self.is_rate_limited(),
+            _ => false,
+        }
+    }
+
+    /// Returns how long the API asked to wait before retrying the request, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match *self {
+            Error::Status(ref error) => error.retry_after,
             _ => None,
         }
     }
@@ -90,6 +240,19 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+#[cfg(feature = "id3")]
+impl From<id3::Error> for Error {
+    fn from(error: id3::Error) -> Error {
+        Error::Tag(error)
+    }
+}
+
+impl From<png::DecodingError> for Error {
+    fn from(error: png::DecodingError) -> Error {
+        Error::Png(error)
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Error {
         Error::Io(error)
@@ -0,0 +1,66 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! API responses shared by the tests of several modules.
+
+use serde_json::{self, Value};
+
+use track::Track;
+
+/// Full representation of a track, as returned by `/tracks/13158665`.
+pub const TRACK: &'static str = r#"{
+    "id": 13158665, "created_at": "2011/04/06 15:37:43 +0000", "user_id": 3699101,
+    "user": {"id": 3699101, "permalink": "alex-stevenson", "username": "Alex Stevenson",
+             "uri": "https://api.soundcloud.com/users/3699101",
+             "permalink_url": "https://soundcloud.com/alex-stevenson",
+             "avatar_url": "https://i1.sndcdn.com/avatars-000004193858-jnf2pd-large.jpg"},
+    "title": "Munching at Tiannas house", "permalink": "munching-at-tiannas-house",
+    "permalink_url": "https://soundcloud.com/alex-stevenson/munching-at-tiannas-house",
+    "uri": "https://api.soundcloud.com/tracks/13158665", "sharing": "public",
+    "embeddable_by": "all", "duration": 18109, "streamable": true, "downloadable": false,
+    "state": "finished", "license": "all-rights-reserved", "genre": "Spoken",
+    "release_year": 2011, "release_month": 4, "release_day": 6, "track_type": "field",
+    "bpm": 120, "key_signature": "", "isrc": "USRC17607839", "label_name": "Tianna Records",
+    "description": "Recorded on an iPhone",
+    "waveform_url": "https://w1.sndcdn.com/fxguEjG4ax6B_m.png",
+    "stream_url": "https://api.soundcloud.com/tracks/13158665/stream",
+    "commentable": true, "comment_count": 0, "download_count": 0, "playback_count": 0,
+    "favoritings_count": 0, "original_format": "m4a", "original_content_size": 10211857
+}"#;
+
+/// Returns the track of `TRACK`.
+pub fn track() -> Track {
+    serde_json::from_str(TRACK).unwrap()
+}
+
+/// Returns `TRACK` as the mini representation embedded in other resources, which has only the
+/// essential fields of the track and its user.
+pub fn mini_track() -> Value {
+    let essential = [
+        "id",
+        "created_at",
+        "user_id",
+        "user",
+        "title",
+        "permalink",
+        "permalink_url",
+        "uri",
+    ];
+    let mut track: Value = serde_json::from_str(TRACK).unwrap();
+
+    if let Value::Object(ref mut fields) = track {
+        fields.retain(|name, _| essential.contains(&name.as_str()));
+
+        if let Some(&mut Value::Object(ref mut user)) = fields.get_mut("user") {
+            user.remove("avatar_url");
+        }
+    }
+
+    track
+}
@@ -0,0 +1,307 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use url::Url;
+
+use error::{Error, Result};
+
+/// Range of bytes within a resource.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteRange {
+    /// Offset of the first byte.
+    pub offset: u64,
+    /// Number of bytes.
+    pub length: u64,
+}
+
+impl ByteRange {
+    /// Returns the offset of the last byte in the range.
+    pub fn last(&self) -> u64 {
+        self.offset + self.length - 1
+    }
+}
+
+/// Segment of a media playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// URL of the resource that holds the segment.
+    pub url: Url,
+    /// Part of the resource that is the segment, or `None` for the whole resource.
+    pub range: Option<ByteRange>,
+}
+
+/// Returns the segments in the m3u8 media `playlist`, in playback order, resolved against
+/// `base_url`.
+///
+/// The initialization section of an `#EXT-X-MAP` tag is returned before the segments, and
+/// segments given as sub-ranges of a resource with `BYTERANGE` or `#EXT-X-BYTERANGE` carry their
+/// byte range. Master playlists and encrypted segments aren't supported.
+pub fn parse_playlist(base_url: &Url, playlist: &str) -> Result<Vec<Segment>> {
+    let mut lines = playlist
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty());
+
+    if lines.next() != Some("#EXTM3U") {
+        return Err(Error::InvalidPlaylist("missing #EXTM3U header".to_owned()));
+    }
+
+    let mut segments = vec![];
+    // Length and offset of the `#EXT-X-BYTERANGE` for the next segment.
+    let mut next_range = None;
+    // Resource and end of the previous sub-range, where a sub-range without an offset starts.
+    let mut previous_end: Option<(Url, u64)> = None;
+
+    for line in lines {
+        if line.starts_with("#EXT-X-STREAM-INF") {
+            return Err(Error::InvalidPlaylist(
+                "master playlists are not supported".to_owned(),
+            ));
+        } else if line.starts_with("#EXT-X-KEY:") {
+            if attribute(line, "METHOD") != Some("NONE") {
+                return Err(Error::InvalidPlaylist(
+                    "encrypted segments are not supported".to_owned(),
+                ));
+            }
+        } else if line.starts_with("#EXT-X-MAP:") {
+            let url = match attribute(line, "URI") {
+                Some(uri) => base_url.join(uri)?,
+                None => {
+                    return Err(Error::InvalidPlaylist(
+                        "missing URI in #EXT-X-MAP".to_owned(),
+                    ))
+                }
+            };
+
+            let range = match attribute(line, "BYTERANGE") {
+                Some(value) => {
+                    let (length, offset) = byte_range(value)?;
+                    Some(ByteRange {
+                        offset: offset.unwrap_or(0),
+                        length: length,
+                    })
+                }
+                None => None,
+            };
+
+            segments.push(Segment {
+                url: url,
+                range: range,
+            });
+        } else if line.starts_with("#EXT-X-BYTERANGE:") {
+            next_range = Some(byte_range(&line["#EXT-X-BYTERANGE:".len()..])?);
+        } else if !line.starts_with('#') {
+            let url = base_url.join(line)?;
+
+            let range =
+                match next_range.take() {
+                    Some((length, Some(offset))) => Some(ByteRange {
+                        offset: offset,
+                        length: length,
+                    }),
+                    Some((length, None)) => match previous_end {
+                        Some((ref previous, end)) if *previous == url => Some(ByteRange {
+                            offset: end,
+                            length: length,
+                        }),
+                        _ => return Err(Error::InvalidPlaylist(
+                            "#EXT-X-BYTERANGE without offset doesn't follow a sub-range of the \
+                             same resource"
+                                .to_owned(),
+                        )),
+                    },
+                    None => None,
+                };
+
+            previous_end = range.map(|range| (url.clone(), range.offset + range.length));
+            segments.push(Segment {
+                url: url,
+                range: range,
+            });
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parses a byte range of the form `<length>[@<offset>]`.
+fn byte_range(value: &str) -> Result<(u64, Option<u64>)> {
+    let invalid = || Error::InvalidPlaylist(format!("invalid byte range: {}", value));
+    let mut parts = value.trim().splitn(2, '@');
+
+    let length = match parts.next().map(str::parse) {
+        Some(Ok(length)) if length > 0 => length,
+        _ => return Err(invalid()),
+    };
+
+    let offset = match parts.next() {
+        Some(offset) => Some(offset.parse().map_err(|_| invalid())?),
+        None => None,
+    };
+
+    Ok((length, offset))
+}
+
+/// Returns the value of the attribute `name` in the attribute list of a `tag` line, without
+/// quotes.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let list = &tag[tag.find(':')? + 1..];
+    let mut rest = list;
+
+    while !rest.is_empty() {
+        let equals = rest.find('=')?;
+        let key = &rest[..equals];
+        let value_rest = &rest[equals + 1..];
+
+        let (value, remainder) = if value_rest.starts_with('"') {
+            let end = value_rest[1..].find('"')? + 1;
+            (&value_rest[1..end], &value_rest[end + 1..])
+        } else {
+            match value_rest.find(',') {
+                Some(comma) => (&value_rest[..comma], &value_rest[comma..]),
+                None => (value_rest, ""),
+            }
+        };
+
+        if key.trim() == name {
+            return Some(value);
+        }
+
+        rest = remainder.trim_start_matches(',');
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_url() -> Url {
+        Url::parse("https://cf-hls-media.sndcdn.com/playlist/abc/playlist.m3u8?policy=x").unwrap()
+    }
+
+    #[test]
+    fn test_parse_playlist() {
+        let playlist = "#EXTM3U\n\
+                        #EXT-X-VERSION:6\n\
+                        #EXT-X-TARGETDURATION:10\n\
+                        #EXTINF:9.952,\n\
+                        https://cf-hls-media.sndcdn.com/media/0/9952/abc.128.mp3?policy=x\n\
+                        #EXTINF:9.978,\n\
+                        /media/9952/19930/abc.128.mp3?policy=x\n\
+                        #EXT-X-ENDLIST\n";
+
+        let segments = parse_playlist(&base_url(), playlist).unwrap();
+
+        assert!(segments.iter().all(|segment| segment.range.is_none()));
+        assert_eq!(
+            segments
+                .iter()
+                .map(|segment| segment.url.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "https://cf-hls-media.sndcdn.com/media/0/9952/abc.128.mp3?policy=x",
+                "https://cf-hls-media.sndcdn.com/media/9952/19930/abc.128.mp3?policy=x",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_playlist_with_map() {
+        let playlist = "#EXTM3U\r\n\
+                        #EXT-X-MAP:URI=\"init.mp4\",BYTERANGE=\"1000@0\"\r\n\
+                        #EXTINF:9.952,\r\n\
+                        segment-0.m4s\r\n";
+
+        let segments = parse_playlist(&base_url(), playlist).unwrap();
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments[0].url.as_str(),
+            "https://cf-hls-media.sndcdn.com/playlist/abc/init.mp4"
+        );
+        assert_eq!(
+            segments[0].range,
+            Some(ByteRange {
+                offset: 0,
+                length: 1000,
+            })
+        );
+        assert_eq!(
+            segments[1].url.as_str(),
+            "https://cf-hls-media.sndcdn.com/playlist/abc/segment-0.m4s"
+        );
+        assert_eq!(segments[1].range, None);
+    }
+
+    #[test]
+    fn test_parse_playlist_with_byte_ranges() {
+        let playlist = "#EXTM3U\n\
+                        #EXT-X-MAP:URI=\"audio.mp4\",BYTERANGE=\"720@0\"\n\
+                        #EXTINF:9.952,\n\
+                        #EXT-X-BYTERANGE:5000@720\n\
+                        audio.mp4\n\
+                        #EXTINF:9.978,\n\
+                        #EXT-X-BYTERANGE:4000\n\
+                        audio.mp4\n";
+
+        let segments = parse_playlist(&base_url(), playlist).unwrap();
+
+        assert_eq!(
+            segments
+                .iter()
+                .map(|segment| segment.range.unwrap())
+                .map(|range| (range.offset, range.last()))
+                .collect::<Vec<_>>(),
+            vec![(0, 719), (720, 5719), (5720, 9719)]
+        );
+    }
+
+    #[test]
+    fn test_parse_playlist_with_invalid_byte_ranges() {
+        assert!(parse_playlist(
+            &base_url(),
+            "#EXTM3U\n#EXT-X-BYTERANGE:4000\n#EXTINF:10,\na.mp4\n"
+        )
+        .is_err());
+        assert!(parse_playlist(
+            &base_url(),
+            "#EXTM3U\n#EXT-X-BYTERANGE:1000@0\na.mp4\n#EXT-X-BYTERANGE:1000\nb.mp4\n"
+        )
+        .is_err());
+        assert!(parse_playlist(&base_url(), "#EXTM3U\n#EXT-X-BYTERANGE:abc\na.mp4\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_playlist() {
+        assert!(parse_playlist(&base_url(), "").is_err());
+        assert!(parse_playlist(&base_url(), "<html></html>").is_err());
+        assert!(parse_playlist(
+            &base_url(),
+            "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=128000\nlow.m3u8\n"
+        )
+        .is_err());
+        assert!(parse_playlist(
+            &base_url(),
+            "#EXTM3U\n#EXT-X-KEY:METHOD=AES-128,URI=\"key\"\n#EXTINF:10,\na.ts\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_attribute() {
+        let tag = "#EXT-X-KEY:METHOD=AES-128,URI=\"https://a/b?c=d,e\",IV=0x1";
+
+        assert_eq!(attribute(tag, "METHOD"), Some("AES-128"));
+        assert_eq!(attribute(tag, "URI"), Some("https://a/b?c=d,e"));
+        assert_eq!(attribute(tag, "IV"), Some("0x1"));
+        assert_eq!(attribute(tag, "KEYFORMAT"), None);
+    }
+}
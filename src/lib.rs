@@ -10,26 +10,64 @@
 //!
 //! This soundcloud library provides an interface where you can query soundcloud for information
 //! about tracks and users.
+extern crate bytes;
+extern crate chrono;
 extern crate futures;
 extern crate hyper;
 extern crate hyper_tls;
+#[cfg(feature = "id3")]
+extern crate id3;
+#[macro_use]
 extern crate log;
+extern crate png;
+extern crate rand;
 extern crate serde;
 extern crate url;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
 extern crate tokio_core;
+extern crate tokio_io;
 
 /// The static host address for the API.
 pub const API_HOST: &'static str = "api.soundcloud.com";
 
+mod artwork;
+mod batch;
 mod client;
+mod date;
 pub mod error;
+#[cfg(test)]
+mod fixtures;
+mod hls;
+mod metadata;
+pub mod oauth;
+mod page;
+mod playlist;
+mod progress;
+mod rate_limit;
+mod retry;
+#[cfg(feature = "id3")]
+pub mod tag;
 mod track;
+mod transcoding;
+mod user;
+mod waveform;
 
 // Re-export commonly used resources.
-pub use client::Client;
-pub use client::{App, Comment, User};
+pub use artwork::{ArtworkUrl, ImageSize};
+pub use batch::{BatchDownloader, BatchEntry, BatchOutcome, BatchReport};
+pub use client::{App, Comment};
+pub use client::{Client, ClientBuilder, Resource};
 pub use error::Error;
+pub use metadata::{EmbeddableBy, License, OriginalFormat, Sharing, State, TrackType};
+pub use oauth::AccessToken;
+pub use page::Page;
+pub use playlist::Playlist;
+pub use progress::Progress;
+pub use rate_limit::{Quota, RateLimitMetrics, RateLimiter};
+pub use retry::RetryPolicy;
 pub use track::Track;
+pub use transcoding::{AudioFormat, Media, Protocol, Transcoding, TranscodingFormat};
+pub use user::{User, WebProfile};
+pub use waveform::Waveform;
@@ -0,0 +1,207 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Typed values of the string fields of tracks.
+
+use std::fmt;
+use std::result;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Defines an enum of the known string values of a field, with an `Unknown` variant that keeps
+/// any other value as it is.
+macro_rules! string_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_attr:meta])* $variant:ident => $value:expr,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_attr])* $variant,)*
+            /// Any value this library doesn't know about.
+            Unknown(String),
+        }
+
+        impl $name {
+            /// Returns the value as it appears in the API.
+            pub fn to_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(ref value) => value,
+                }
+            }
+        }
+
+        impl<'a> From<&'a str> for $name {
+            fn from(value: &'a str) -> $name {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value.to_owned()),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.to_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.to_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> result::Result<$name, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let value = String::deserialize(deserializer)?;
+                Ok($name::from(value.as_str()))
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Who can see a track.
+    pub enum Sharing {
+        /// Everyone.
+        Public => "public",
+        /// Only the uploader and users it has been shared with.
+        Private => "private",
+    }
+}
+
+string_enum! {
+    /// Who can embed a track.
+    pub enum EmbeddableBy {
+        /// Everyone.
+        All => "all",
+        /// Only the uploader.
+        Me => "me",
+        /// Nobody.
+        Nobody => "none",
+    }
+}
+
+string_enum! {
+    /// Encoding state of an uploaded track.
+    pub enum State {
+        /// The upload is still being encoded.
+        Processing => "processing",
+        /// Encoding the upload failed.
+        Failed => "failed",
+        /// The track has been encoded and can be played.
+        Finished => "finished",
+    }
+}
+
+string_enum! {
+    /// License of a track.
+    pub enum License {
+        /// No rights reserved.
+        NoRightsReserved => "no-rights-reserved",
+        /// All rights reserved.
+        AllRightsReserved => "all-rights-reserved",
+        /// Creative Commons Attribution.
+        CcBy => "cc-by",
+        /// Creative Commons Attribution-NonCommercial.
+        CcByNc => "cc-by-nc",
+        /// Creative Commons Attribution-NoDerivs.
+        CcByNd => "cc-by-nd",
+        /// Creative Commons Attribution-ShareAlike.
+        CcBySa => "cc-by-sa",
+        /// Creative Commons Attribution-NonCommercial-NoDerivs.
+        CcByNcNd => "cc-by-nc-nd",
+        /// Creative Commons Attribution-NonCommercial-ShareAlike.
+        CcByNcSa => "cc-by-nc-sa",
+    }
+}
+
+string_enum! {
+    /// Kind of recording a track is.
+    pub enum TrackType {
+        /// Original work.
+        Original => "original",
+        /// Remix of another track.
+        Remix => "remix",
+        /// Live performance.
+        Live => "live",
+        /// Field recording.
+        Recording => "recording",
+        /// Spoken word.
+        Spoken => "spoken",
+        /// Podcast episode.
+        Podcast => "podcast",
+        /// Demo.
+        Demo => "demo",
+        /// Work in progress.
+        InProgress => "in progress",
+        /// Stem of a track.
+        Stem => "stem",
+        /// Loop.
+        Loop => "loop",
+        /// Sound effect.
+        SoundEffect => "sound effect",
+        /// Sample.
+        Sample => "sample",
+        /// Anything else.
+        Other => "other",
+    }
+}
+
+string_enum! {
+    /// File format of an uploaded track.
+    pub enum OriginalFormat {
+        /// Audio Interchange File Format.
+        Aiff => "aiff",
+        /// Free Lossless Audio Codec.
+        Flac => "flac",
+        /// MPEG-4 audio.
+        M4a => "m4a",
+        /// MPEG-1 Audio Layer III.
+        Mp3 => "mp3",
+        /// Ogg Vorbis.
+        Ogg => "ogg",
+        /// Waveform Audio File Format.
+        Wav => "wav",
+        /// Raw audio.
+        Raw => "raw",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn test_known_value() {
+        let license: License = serde_json::from_str(r#""cc-by-nc-sa""#).unwrap();
+
+        assert_eq!(license, License::CcByNcSa);
+        assert_eq!(serde_json::to_string(&license).unwrap(), r#""cc-by-nc-sa""#);
+        assert_eq!(TrackType::from("in progress"), TrackType::InProgress);
+    }
+
+    #[test]
+    fn test_unknown_value() {
+        let state: State = serde_json::from_str(r#""storing""#).unwrap();
+
+        assert_eq!(state, State::Unknown("storing".to_owned()));
+        assert_eq!(state.to_str(), "storing");
+        assert_eq!(serde_json::to_string(&state).unwrap(), r#""storing""#);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use serde_json;
+
+use error::{Error, Result};
+
+/// The page users are sent to in order to authorize an application.
+pub const CONNECT_URL: &'static str = "https://soundcloud.com/connect";
+
+/// OAuth2 access token, as returned by the `/oauth2/token` endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccessToken {
+    /// The token to send in the `Authorization` header.
+    pub access_token: String,
+    /// Number of seconds the token is valid for, if it expires.
+    pub expires_in: Option<u64>,
+    /// Space-separated list of granted scopes.
+    pub scope: Option<String>,
+    /// Token that can be exchanged for a new access token once this one expires.
+    pub refresh_token: Option<String>,
+}
+
+impl AccessToken {
+    /// Constructs a new access token with no expiry or refresh token.
+    pub fn new<S: Into<String>>(access_token: S) -> AccessToken {
+        AccessToken {
+            access_token: access_token.into(),
+            expires_in: None,
+            scope: None,
+            refresh_token: None,
+        }
+    }
+
+    /// Returns the value of the `Authorization` header for this token.
+    pub fn authorization(&self) -> String {
+        format!("OAuth {}", self.access_token)
+    }
+}
+
+/// OAuth2 grant that can be exchanged for an access token.
+#[derive(Debug, Clone)]
+pub enum Grant {
+    /// Authorization code received on the redirect uri.
+    AuthorizationCode(String),
+    /// The application's own credentials.
+    ClientCredentials,
+    /// Refresh token from a previously issued access token.
+    RefreshToken(String),
+}
+
+impl Grant {
+    /// Returns the `grant_type` value for this grant.
+    pub fn grant_type(&self) -> &str {
+        match *self {
+            Grant::AuthorizationCode(_) => "authorization_code",
+            Grant::ClientCredentials => "client_credentials",
+            Grant::RefreshToken(_) => "refresh_token",
+        }
+    }
+}
+
+/// Parses the body of a token endpoint response.
+pub fn parse_token_response(body: &[u8]) -> Result<AccessToken> {
+    let value: serde_json::Value = serde_json::from_slice(body)?;
+
+    if value.get("access_token").is_some() {
+        return Ok(serde_json::from_value(value)?);
+    }
+
+    match value.get("error").and_then(|error| error.as_str()) {
+        Some(error) => Err(Error::ApiError(error.to_owned())),
+        None => Err(Error::ApiError("expected access token".to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_response() {
+        let token = parse_token_response(
+            br#"{"access_token": "1-2-3", "expires_in": 21599, "scope": "*",
+                 "refresh_token": "4-5-6"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(token.access_token, "1-2-3");
+        assert_eq!(token.expires_in, Some(21599));
+        assert_eq!(token.scope, Some("*".to_owned()));
+        assert_eq!(token.refresh_token, Some("4-5-6".to_owned()));
+        assert_eq!(token.authorization(), "OAuth 1-2-3");
+    }
+
+    #[test]
+    fn test_parse_non_expiring_token_response() {
+        let token = parse_token_response(br#"{"access_token": "1-2-3"}"#).unwrap();
+
+        assert_eq!(token, AccessToken::new("1-2-3"));
+    }
+
+    #[test]
+    fn test_parse_token_error_response() {
+        match parse_token_response(br#"{"error": "invalid_grant"}"#) {
+            Err(Error::ApiError(ref error)) => assert_eq!(error, "invalid_grant"),
+            _ => panic!("expected an API error"),
+        }
+
+        assert!(parse_token_response(br#"{}"#).is_err());
+        assert!(parse_token_response(b"<html>").is_err());
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use futures::stream;
+use futures::{Future, Stream};
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
+use url::Url;
+
+use client::Client;
+use error::{Error, Result};
+
+/// The default number of items requested per page.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// The maximum number of items the API returns per page.
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// Single page of a collection, as returned when `linked_partitioning` is enabled.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    /// Items on this page.
+    pub collection: Vec<T>,
+    /// API resource URL of the next page, if there is one.
+    pub next_href: Option<String>,
+}
+
+/// Deserializes every item in `items`, skipping and logging those that fail to deserialize so
+/// that a single malformed item doesn't fail the whole collection.
+///
+/// Fails with the last error if `items` isn't empty but none of them deserialize, as the
+/// collection then doesn't have the expected schema at all.
+pub fn deserialize_items<T: DeserializeOwned>(items: Vec<Value>) -> Result<Vec<T>> {
+    let mut deserialized = Vec::with_capacity(items.len());
+    let mut last_error = None;
+
+    for item in items {
+        match serde_json::from_value(item) {
+            Ok(item) => deserialized.push(item),
+            Err(error) => {
+                warn!("skipping item that failed to deserialize: {}", error);
+                last_error = Some(error);
+            }
+        }
+    }
+
+    match last_error {
+        Some(error) if deserialized.is_empty() => Err(Error::JsonError(error)),
+        _ => Ok(deserialized),
+    }
+}
+
+/// Returns a stream of every item in the paginated collection at `url`.
+///
+/// Pages are requested lazily by following the `next_href` cursor of each page, and no more
+/// pages are requested once `max_items` items have been yielded. Items that fail to deserialize
+/// are skipped, but a page on which no item deserializes fails the stream, see
+/// `deserialize_items`.
+pub fn paginate<'a, T>(
+    client: &'a Client,
+    url: Url,
+    max_items: Option<u64>,
+) -> Box<Stream<Item = T, Error = Error> + 'a>
+where
+    T: DeserializeOwned + 'static,
+{
+    let pages = stream::unfold(Some(url), move |next| {
+        next.map(|url| {
+            client.get_json(url.as_str()).and_then(|page: Page<Value>| {
+                let next = if page.collection.is_empty() {
+                    None
+                } else {
+                    page.next_href.and_then(|href| Url::parse(&href).ok())
+                };
+
+                deserialize_items::<T>(page.collection).map(|items| (items, next))
+            })
+        })
+    });
+
+    let items = pages.map(stream::iter_ok).flatten();
+
+    match max_items {
+        Some(max_items) => Box::new(items.take(max_items)),
+        None => Box::new(items),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Item {
+        id: u64,
+    }
+
+    #[test]
+    fn test_deserialize_items_skips_failures() {
+        let items: Value =
+            serde_json::from_str(r#"[{"id": 1}, {"id": "two"}, {}, {"id": 3}]"#).unwrap();
+        let items: Vec<Item> = match items {
+            Value::Array(items) => deserialize_items(items).unwrap(),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(items, vec![Item { id: 1 }, Item { id: 3 }]);
+    }
+
+    #[test]
+    fn test_deserialize_items_fails_without_items() {
+        let items: Value = serde_json::from_str(r#"[{"id": "one"}, {"name": "two"}]"#).unwrap();
+        let items = match items {
+            Value::Array(items) => deserialize_items::<Item>(items),
+            _ => unreachable!(),
+        };
+
+        match items {
+            Err(Error::JsonError(_)) => (),
+            _ => panic!("expected a json error"),
+        }
+
+        assert_eq!(deserialize_items::<Item>(vec![]).unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::{Future, Stream};
+use serde_json;
+use url::Url;
+
+use artwork::ArtworkUrl;
+use client::Client;
+use date;
+use error::Error;
+use page::{self, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use track::Track;
+use user::User;
+
+/// Playlist, or set, of tracks.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Playlist {
+    /// Integer ID.
+    pub id: u64,
+    /// Time of which the playlist was created.
+    #[serde(with = "date")]
+    pub created_at: DateTime<Utc>,
+    /// User ID of the creator.
+    pub user_id: u64,
+    /// Small representation of the creators user.
+    pub user: User,
+    /// Title.
+    pub title: String,
+    /// Permalink of the resource.
+    pub permalink: String,
+    /// URL to the SoundCloud.com page.
+    pub permalink_url: String,
+    /// API resource URL.
+    pub uri: String,
+    /// Sharing status.
+    pub sharing: String,
+    /// Who can embed this playlist.
+    pub embeddable_by: Option<String>,
+    /// External purchase link.
+    pub purchase_url: Option<String>,
+    /// URL to a JPEG image.
+    pub artwork_url: Option<ArtworkUrl>,
+    /// HTML description.
+    pub description: Option<String>,
+    /// Representation of a labels user.
+    pub label: Option<serde_json::Value>,
+    /// Duration in milliseconds.
+    pub duration: u64,
+    /// Genre.
+    pub genre: Option<String>,
+    /// List of tags.
+    pub tag_list: Option<String>,
+    /// Label user ID.
+    pub label_id: Option<u64>,
+    /// Label user name.
+    pub label_name: Option<String>,
+    /// Release number.
+    pub release: Option<String>,
+    /// Day of the release.
+    pub release_day: Option<u64>,
+    /// Month of the release.
+    pub release_month: Option<u64>,
+    /// Year of the release.
+    pub release_year: Option<u64>,
+    /// If the playlist is available for stream via the API.
+    pub streamable: Option<bool>,
+    /// If the playlist is available for download.
+    pub downloadable: Option<bool>,
+    /// EAN identifier for the playlist.
+    pub ean: Option<String>,
+    /// Playlist type, e.g. `album` or `compilation`.
+    pub playlist_type: Option<String>,
+    /// Purchase title.
+    pub purchase_title: Option<String>,
+    /// Creative common license.
+    pub license: Option<String>,
+    /// Number of tracks.
+    pub track_count: Option<u64>,
+    /// Tracks in the playlist.
+    pub tracks: Vec<Track>,
+}
+
+#[derive(Debug)]
+pub struct PlaylistRequestBuilder<'a> {
+    client: &'a Client,
+    query: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SinglePlaylistRequestBuilder<'a> {
+    client: &'a Client,
+    pub id: usize,
+}
+
+impl<'a> SinglePlaylistRequestBuilder<'a> {
+    /// Constructs a new playlist request.
+    pub fn new(client: &'a Client, id: usize) -> SinglePlaylistRequestBuilder<'a> {
+        SinglePlaylistRequestBuilder {
+            client: client,
+            id: id,
+        }
+    }
+
+    /// Sends the request and returns the playlist.
+    pub fn get(&mut self) -> Box<Future<Item = Playlist, Error = Error>> {
+        self.client.get_json(self.request_url().as_str())
+    }
+
+    pub fn request_url(&self) -> Url {
+        let url = self.client.api_url(&format!("/playlists/{}", self.id));
+
+        url
+    }
+}
+
+impl<'a> PlaylistRequestBuilder<'a> {
+    /// Creates a new playlist request builder, with no set parameters.
+    pub fn new(client: &'a Client) -> PlaylistRequestBuilder<'a> {
+        PlaylistRequestBuilder {
+            client: client,
+            query: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Sets the search query filter, which will only return playlists with a matching query.
+    pub fn query<S>(&mut self, query: Option<S>) -> &mut PlaylistRequestBuilder<'a>
+    where
+        S: AsRef<str>,
+    {
+        self.query = query.map(|s| s.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the maximum number of playlists per page, up to 200.
+    pub fn limit(&mut self, limit: Option<usize>) -> &mut PlaylistRequestBuilder<'a> {
+        self.limit = limit.map(|limit| cmp::min(limit, MAX_PAGE_SIZE));
+        self
+    }
+
+    /// Sets the number of playlists to skip before the first returned playlist.
+    pub fn offset(&mut self, offset: Option<usize>) -> &mut PlaylistRequestBuilder<'a> {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns a builder for a single playlist.
+    pub fn id(&self, id: usize) -> SinglePlaylistRequestBuilder<'a> {
+        SinglePlaylistRequestBuilder::new(self.client, id)
+    }
+
+    /// Performs the request and returns a list of playlists if there are any results, None
+    /// otherwise, or an error if one occurred.
+    ///
+    /// Playlists that fail to deserialize are skipped and logged, but the request fails if none
+    /// of them deserialize.
+    pub fn get(&mut self) -> Box<Future<Item = Option<Vec<Playlist>>, Error = Error>> {
+        let playlist_list = self.client.get_json(self.request_url().as_str()).and_then(
+            |playlist_list: Vec<serde_json::Value>| {
+                if playlist_list.is_empty() {
+                    Ok(None)
+                } else {
+                    page::deserialize_items(playlist_list).map(Some)
+                }
+            },
+        );
+
+        Box::new(playlist_list)
+    }
+
+    /// Returns a stream of every playlist matching the request, following the pagination cursors
+    /// until the results are exhausted or `max_items` playlists have been returned.
+    pub fn paginate(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = Playlist, Error = Error> + 'a> {
+        let mut url = self.request_url();
+
+        if self.limit.is_none() {
+            url.query_pairs_mut()
+                .append_pair("limit", &DEFAULT_PAGE_SIZE.to_string());
+        }
+
+        url.query_pairs_mut()
+            .append_pair("linked_partitioning", "1");
+
+        page::paginate(self.client, url, max_items)
+    }
+
+    /// Returns the url of the request, without the `client_id` parameter.
+    pub fn request_url(&self) -> Url {
+        let mut url = self.client.api_url("/playlists");
+        let params = self.request_params();
+
+        if !params.is_empty() {
+            url.query_pairs_mut().extend_pairs(params);
+        }
+
+        url
+    }
+
+    fn request_params(&self) -> Vec<(&str, String)> {
+        let mut result = vec![];
+
+        if let Some(ref query) = self.query {
+            result.push(("q", query.clone()));
+        }
+
+        if let Some(limit) = self.limit {
+            result.push(("limit", limit.to_string()));
+        }
+
+        if let Some(offset) = self.offset {
+            result.push(("offset", offset.to_string()));
+        }
+
+        result
+    }
+}
+
+impl Playlist {
+    /// Returns the release date, if the day, month and year of the release are all set.
+    pub fn release_date(&self) -> Option<NaiveDate> {
+        date::from_parts(self.release_year, self.release_month, self.release_day)
+    }
+}
+
+impl PartialEq for Playlist {
+    fn eq(&self, other: &Playlist) -> bool {
+        other.id == self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_core;
+
+    fn client(handle: &tokio_core::reactor::Handle) -> Client {
+        Client::new("abc", handle)
+    }
+
+    #[test]
+    fn test_search_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client
+            .playlists()
+            .query(Some("noisia"))
+            .limit(Some(500))
+            .offset(Some(50))
+            .request_url();
+
+        assert_eq!(url.path(), "/playlists");
+        assert_eq!(url.query(), Some("q=noisia&limit=200&offset=50"));
+    }
+
+    #[test]
+    fn test_empty_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client.playlists().query(None::<&str>).request_url();
+
+        assert_eq!(url.query(), None);
+    }
+
+    #[test]
+    fn test_single_playlist_url() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+
+        assert_eq!(
+            client.playlist(405726).request_url().as_str(),
+            "https://api.soundcloud.com/playlists/405726"
+        );
+        assert_eq!(
+            client.playlists().id(405726).request_url().as_str(),
+            "https://api.soundcloud.com/playlists/405726"
+        );
+    }
+}
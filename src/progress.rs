@@ -0,0 +1,75 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::time::Duration;
+
+/// Progress of a download or stream, reported after every chunk that is written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Number of bytes written so far.
+    pub bytes: u64,
+    /// Total number of bytes, if the response had a `Content-Length` header.
+    pub total: Option<u64>,
+    /// Time since the response was received.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the average number of bytes written per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs() as f64 + self.elapsed.subsec_nanos() as f64 * 1e-9;
+
+        if secs > 0.0 {
+            self.bytes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the fraction of the total that has been written, between 0 and 1, if the total is
+    /// known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total.map(|total| {
+            if total > 0 {
+                (self.bytes as f64 / total as f64).min(1.0)
+            } else {
+                1.0
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throughput() {
+        let progress = Progress {
+            bytes: 3000,
+            total: Some(12000),
+            elapsed: Duration::from_millis(1500),
+        };
+
+        assert_eq!(progress.throughput(), 2000.0);
+        assert_eq!(progress.fraction(), Some(0.25));
+    }
+
+    #[test]
+    fn test_unknown_total() {
+        let progress = Progress {
+            bytes: 3000,
+            total: None,
+            elapsed: Duration::from_secs(0),
+        };
+
+        assert_eq!(progress.throughput(), 0.0);
+        assert_eq!(progress.fraction(), None);
+    }
+}
@@ -0,0 +1,291 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use futures::future;
+use futures::Future;
+use tokio_core::reactor::{Handle, Timeout};
+
+use error::Error;
+
+/// Number of requests allowed per time window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    /// Number of requests allowed per window.
+    pub requests: u32,
+    /// Length of the window.
+    pub window: Duration,
+}
+
+impl Quota {
+    /// Constructs a quota of `requests` requests per `window`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` or `window` is zero, as no request could ever be sent.
+    pub fn new(requests: u32, window: Duration) -> Quota {
+        let quota = Quota {
+            requests: requests,
+            window: window,
+        };
+
+        quota.validate();
+        quota
+    }
+
+    /// Panics if the quota allows no requests at all.
+    fn validate(&self) {
+        assert!(self.requests > 0, "quota must allow at least one request");
+        assert!(
+            self.window > Duration::from_secs(0),
+            "quota window must not be zero"
+        );
+    }
+}
+
+/// Statistics about how much requests have been delayed by a `RateLimiter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitMetrics {
+    /// Number of requests that passed through the limiter.
+    pub requests: u64,
+    /// Number of requests that had to wait before being sent.
+    pub delayed_requests: u64,
+    /// Total time requests have spent waiting.
+    pub total_wait: Duration,
+    /// Longest time a single request has waited.
+    pub max_wait: Duration,
+}
+
+impl RateLimitMetrics {
+    fn new() -> RateLimitMetrics {
+        RateLimitMetrics {
+            requests: 0,
+            delayed_requests: 0,
+            total_wait: Duration::from_secs(0),
+            max_wait: Duration::from_secs(0),
+        }
+    }
+}
+
+/// Token bucket that is refilled continuously at the rate of its quota.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    tokens_per_second: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(quota: Quota, now: Instant) -> Bucket {
+        quota.validate();
+        let capacity = quota.requests as f64;
+
+        Bucket {
+            capacity: capacity,
+            tokens: capacity,
+            tokens_per_second: capacity / duration_secs(quota.window),
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token and returns how long to wait until it is available.
+    ///
+    /// The token count is allowed to go negative so that waiting requests are queued in the
+    /// order they were reserved.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        if now > self.last_refill {
+            let elapsed = duration_secs(now - self.last_refill);
+
+            self.tokens = (self.tokens + elapsed * self.tokens_per_second).min(self.capacity);
+            self.last_refill = now;
+        }
+
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            secs_duration(-self.tokens / self.tokens_per_second)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    global: Option<Bucket>,
+    endpoints: Vec<(String, Bucket)>,
+    metrics: RateLimitMetrics,
+}
+
+/// Client-side token bucket rate limiter.
+///
+/// Every request reserves a token from the global bucket and from the bucket of the first
+/// endpoint whose path prefix matches the request, and is delayed until both are available.
+/// Clones share the same buckets, so one limiter can be shared by several clients.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl RateLimiter {
+    /// Constructs a rate limiter that allows requests to any endpoint at the rate of `quota`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quota` allows no requests, see `Quota::new`.
+    pub fn new(quota: Quota) -> RateLimiter {
+        let limiter = RateLimiter::unlimited();
+        limiter.inner.borrow_mut().global = Some(Bucket::new(quota, Instant::now()));
+        limiter
+    }
+
+    /// Constructs a rate limiter with no global quota, for use with per-endpoint quotas only.
+    pub fn unlimited() -> RateLimiter {
+        RateLimiter {
+            inner: Rc::new(RefCell::new(Inner {
+                global: None,
+                endpoints: vec![],
+                metrics: RateLimitMetrics::new(),
+            })),
+        }
+    }
+
+    /// Adds a separate quota for requests whose path starts with `path_prefix`, e.g. `/tracks`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quota` allows no requests, see `Quota::new`.
+    pub fn add_endpoint(&self, path_prefix: &str, quota: Quota) {
+        self.inner
+            .borrow_mut()
+            .endpoints
+            .push((path_prefix.to_owned(), Bucket::new(quota, Instant::now())));
+    }
+
+    /// Returns statistics about how much requests have been delayed so far.
+    pub fn metrics(&self) -> RateLimitMetrics {
+        self.inner.borrow().metrics
+    }
+
+    /// Reserves a token for a request to `path` and returns a future that resolves once the
+    /// request may be sent.
+    pub fn acquire(&self, path: &str, handle: &Handle) -> Box<Future<Item = (), Error = Error>> {
+        let wait = self.reserve(path, Instant::now());
+
+        if wait == Duration::from_secs(0) {
+            return Box::new(future::ok(()));
+        }
+
+        match Timeout::new(wait, handle) {
+            Ok(timeout) => Box::new(timeout.map_err(|error| Error::Io(error))),
+            Err(error) => Box::new(future::err(Error::Io(error))),
+        }
+    }
+
+    /// Reserves a token for a request to `path` at `now` and returns how long to wait.
+    fn reserve(&self, path: &str, now: Instant) -> Duration {
+        let mut inner = self.inner.borrow_mut();
+        let mut wait = Duration::from_secs(0);
+
+        if let Some(ref mut bucket) = inner.global {
+            wait = bucket.reserve(now);
+        }
+
+        if let Some(&mut (_, ref mut bucket)) = inner
+            .endpoints
+            .iter_mut()
+            .find(|&&mut (ref prefix, _)| path.starts_with(prefix.as_str()))
+        {
+            wait = wait.max(bucket.reserve(now));
+        }
+
+        let metrics = &mut inner.metrics;
+        metrics.requests += 1;
+
+        if wait > Duration::from_secs(0) {
+            metrics.delayed_requests += 1;
+            metrics.total_wait += wait;
+            metrics.max_wait = metrics.max_wait.max(wait);
+        }
+
+        wait
+    }
+}
+
+fn duration_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9
+}
+
+fn secs_duration(secs: f64) -> Duration {
+    Duration::new(secs.trunc() as u64, (secs.fract() * 1e9) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_quota() {
+        let limiter = RateLimiter::new(Quota::new(2, Duration::from_secs(1)));
+        let now = limiter.inner.borrow().global.as_ref().unwrap().last_refill;
+
+        assert_eq!(limiter.reserve("/tracks", now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve("/users", now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve("/tracks", now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve("/tracks", now), Duration::from_millis(1000));
+        assert_eq!(
+            limiter.reserve("/tracks", now + Duration::from_secs(1)),
+            Duration::from_millis(500)
+        );
+
+        let metrics = limiter.metrics();
+
+        assert_eq!(metrics.requests, 5);
+        assert_eq!(metrics.delayed_requests, 3);
+        assert_eq!(metrics.total_wait, Duration::from_millis(2000));
+        assert_eq!(metrics.max_wait, Duration::from_millis(1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one request")]
+    fn test_quota_without_requests() {
+        Quota::new(0, Duration::from_secs(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "window must not be zero")]
+    fn test_quota_without_window() {
+        Quota::new(10, Duration::from_secs(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one request")]
+    fn test_limiter_with_invalid_quota() {
+        RateLimiter::new(Quota {
+            requests: 0,
+            window: Duration::from_secs(1),
+        });
+    }
+
+    #[test]
+    fn test_endpoint_quota() {
+        let now = Instant::now();
+        let limiter = RateLimiter::unlimited();
+        limiter.add_endpoint("/resolve", Quota::new(1, Duration::from_secs(10)));
+
+        assert_eq!(limiter.reserve("/resolve", now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve("/tracks", now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve("/tracks", now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve("/resolve", now), Duration::from_secs(10));
+    }
+}
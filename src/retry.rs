@@ -0,0 +1,194 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp;
+use std::time::Duration;
+
+use futures::future::{self, Either, Loop};
+use futures::Future;
+use hyper;
+use rand;
+use tokio_core::reactor::{Handle, Timeout};
+
+use error::Error;
+
+/// Policy for retrying requests that failed because of a transient error, such as a connection
+/// reset, a server error or a rate limited response.
+///
+/// Only requests with idempotent methods are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, which is doubled for every following retry.
+    pub base_delay: Duration,
+    /// Upper bound for the delay between two attempts.
+    pub max_delay: Duration,
+    /// Whether to randomize each delay to between half and all of its length.
+    pub jitter: bool,
+    /// Whether to wait for as long as a `Retry-After` header asks instead of the computed delay,
+    /// up to `max_delay`.
+    pub respect_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// Constructs a policy that makes up to `max_attempts` attempts, with a base delay of half a
+    /// second, jitter and `Retry-After` support.
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            respect_retry_after: true,
+        }
+    }
+
+    /// Constructs a policy that never retries.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy::new(1)
+    }
+
+    /// Returns the delay to wait before the next attempt, after `attempt` attempts failed with
+    /// `error`.
+    pub fn delay(&self, attempt: u32, error: &Error) -> Duration {
+        if self.respect_retry_after {
+            if let Some(retry_after) = error.retry_after() {
+                return cmp::min(retry_after, self.max_delay);
+            }
+        }
+
+        let base_millis = duration_millis(self.base_delay);
+        let max_millis = duration_millis(self.max_delay);
+        let exponent = cmp::min(attempt.saturating_sub(1), 32);
+        let mut millis = cmp::min(base_millis.saturating_mul(1 << exponent), max_millis);
+
+        if self.jitter {
+            millis = millis / 2 + (rand::random::<f64>() * (millis / 2) as f64) as u64;
+        }
+
+        Duration::from_millis(millis)
+    }
+}
+
+/// Returns true if requests with the given method can safely be sent more than once.
+pub fn is_idempotent(method: &hyper::Method) -> bool {
+    match *method {
+        hyper::Method::Get
+        | hyper::Method::Head
+        | hyper::Method::Put
+        | hyper::Method::Delete
+        | hyper::Method::Options
+        | hyper::Method::Trace => true,
+        _ => false,
+    }
+}
+
+/// Runs the future returned by `action`, and runs it again after a delay for as long as it fails
+/// with a transient error and `policy` allows another attempt.
+pub fn retry<'a, F, R>(
+    handle: &Handle,
+    policy: &RetryPolicy,
+    mut action: F,
+) -> Box<Future<Item = R::Item, Error = Error> + 'a>
+where
+    F: FnMut() -> R + 'a,
+    R: Future<Error = Error> + 'a,
+    R::Item: 'a,
+{
+    let handle = handle.clone();
+    let policy = policy.clone();
+
+    let result = future::loop_fn(1, move |attempt| {
+        let handle = handle.clone();
+        let policy = policy.clone();
+
+        action().then(move |result| match result {
+            Ok(item) => Either::A(future::ok(Loop::Break(item))),
+            Err(error) => {
+                if attempt >= policy.max_attempts || !error.is_transient() {
+                    return Either::A(future::err(error));
+                }
+
+                let delay = policy.delay(attempt, &error);
+
+                match Timeout::new(delay, &handle) {
+                    Ok(timeout) => Either::B(
+                        timeout
+                            .map(move |_| Loop::Continue(attempt + 1))
+                            .map_err(|error| Error::Io(error)),
+                    ),
+                    Err(error) => Either::A(future::err(Error::Io(error))),
+                }
+            }
+        })
+    });
+
+    Box::new(result)
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_delay() {
+        let mut policy = RetryPolicy::new(5);
+        policy.jitter = false;
+
+        let error = Error::ApiError("test".to_owned());
+
+        assert_eq!(policy.delay(1, &error), Duration::from_millis(500));
+        assert_eq!(policy.delay(2, &error), Duration::from_millis(1000));
+        assert_eq!(policy.delay(3, &error), Duration::from_millis(2000));
+        assert_eq!(policy.delay(10, &error), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_retry_after_delay() {
+        use error::StatusError;
+
+        let policy = RetryPolicy::new(5);
+        let error = |retry_after| {
+            Error::Status(StatusError {
+                status: hyper::StatusCode::TooManyRequests,
+                url: "https://api.soundcloud.com/tracks".to_owned(),
+                body: None,
+                retry_after: Some(retry_after),
+                headers: hyper::Headers::new(),
+            })
+        };
+
+        assert_eq!(
+            policy.delay(1, &error(Duration::from_secs(5))),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            policy.delay(1, &error(Duration::from_secs(86400))),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_jittered_delay() {
+        let policy = RetryPolicy::new(5);
+        let error = Error::ApiError("test".to_owned());
+
+        for _ in 0..100 {
+            let delay = policy.delay(2, &error);
+
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_millis(1000));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::path::Path;
+
+use id3::frame::{Comment, Picture, PictureType};
+use id3::{self, TagLike, Version};
+
+use error::Result;
+use track::Track;
+
+/// Writes ID3v2.4 tags with the metadata of `track` to the MP3 file at `path`, with `artwork` as
+/// the front cover if given.
+///
+/// Frames that the track has no value for are left as they are in an existing tag.
+pub fn write_tags<P: AsRef<Path>>(path: P, track: &Track, artwork: Option<&[u8]>) -> Result<()> {
+    let path = path.as_ref();
+    let mut tag = id3::no_tag_ok(id3::Tag::read_from_path(path))?.unwrap_or_else(id3::Tag::new);
+
+    set_track_frames(&mut tag, track, artwork);
+    tag.write_to_path(path, Version::Id3v24)?;
+
+    Ok(())
+}
+
+/// Sets the frames of `tag` from the fields of `track`.
+fn set_track_frames(tag: &mut id3::Tag, track: &Track, artwork: Option<&[u8]>) {
+    tag.set_title(track.title.as_str());
+    tag.set_artist(track.user.username.as_str());
+
+    if let Some(ref genre) = track.genre {
+        tag.set_genre(genre.as_str());
+    }
+
+    if let Some(year) = track.release_year {
+        tag.set_year(year as i32);
+    }
+
+    if let Some(bpm) = track.bpm {
+        tag.set_text("TBPM", bpm.to_string());
+    }
+
+    let texts = [
+        ("TKEY", &track.key_signature),
+        ("TSRC", &track.isrc),
+        ("TPUB", &track.label_name),
+    ];
+
+    for &(id, value) in texts.iter() {
+        if let Some(ref value) = *value {
+            if !value.is_empty() {
+                tag.set_text(id, value.as_str());
+            }
+        }
+    }
+
+    if let Some(ref description) = track.description {
+        if !description.is_empty() {
+            tag.add_frame(Comment {
+                lang: "eng".to_owned(),
+                description: String::new(),
+                text: description.clone(),
+            });
+        }
+    }
+
+    if let Some(artwork) = artwork {
+        tag.remove_picture_by_type(PictureType::CoverFront);
+        tag.add_frame(Picture {
+            mime_type: image_mime_type(artwork).to_owned(),
+            picture_type: PictureType::CoverFront,
+            description: String::new(),
+            data: artwork.to_vec(),
+        });
+    }
+}
+
+/// Returns the MIME type of an image, as determined by its signature.
+fn image_mime_type(image: &[u8]) -> &'static str {
+    if image.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else {
+        "image/jpeg"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures;
+
+    #[test]
+    fn test_track_frames() {
+        let track = fixtures::track();
+
+        let mut tag = id3::Tag::new();
+        set_track_frames(&mut tag, &track, Some(b"\xff\xd8\xff\xe0"));
+
+        assert_eq!(tag.title(), Some("Munching at Tiannas house"));
+        assert_eq!(tag.artist(), Some("Alex Stevenson"));
+        assert_eq!(tag.genre(), Some("Spoken"));
+        assert_eq!(tag.year(), Some(2011));
+        assert_eq!(
+            tag.get("TBPM").and_then(|frame| frame.content().text()),
+            Some("120")
+        );
+        assert!(tag.get("TKEY").is_none());
+        assert_eq!(
+            tag.get("TSRC").and_then(|frame| frame.content().text()),
+            Some("USRC17607839")
+        );
+        assert_eq!(
+            tag.get("TPUB").and_then(|frame| frame.content().text()),
+            Some("Tianna Records")
+        );
+        assert_eq!(
+            tag.comments().next().map(|comment| comment.text.as_str()),
+            Some("Recorded on an iPhone")
+        );
+
+        let picture = tag.pictures().next().unwrap();
+        assert_eq!(picture.mime_type, "image/jpeg");
+        assert_eq!(picture.picture_type, PictureType::CoverFront);
+    }
+
+    #[test]
+    fn test_image_mime_type() {
+        assert_eq!(image_mime_type(b"\x89PNG\r\n\x1a\n\0\0"), "image/png");
+        assert_eq!(image_mime_type(b"\xff\xd8\xff\xe0"), "image/jpeg");
+    }
+}
@@ -7,16 +7,24 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::cmp;
 use std::fmt;
 use std::str;
 
+use chrono::{DateTime, NaiveDate, Utc};
 use futures::future;
 use futures::{Future, Stream};
 use serde_json;
 use url::Url;
 
-use client::{App, Client, User};
+use artwork::ArtworkUrl;
+use client::{App, Client};
+use date;
 use error::{Error, Result};
+use metadata::{EmbeddableBy, License, OriginalFormat, Sharing, State, TrackType};
+use page::{self, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use transcoding::{self, AudioFormat, Media, Protocol, Transcoding};
+use user::User;
 
 #[derive(Debug)]
 pub enum Filter {
@@ -54,13 +62,45 @@ impl Filter {
     }
 }
 
+/// Inclusive range filter that can be left open on either side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    /// Lower bound, if any.
+    pub from: Option<u64>,
+    /// Upper bound, if any.
+    pub to: Option<u64>,
+}
+
+impl Range {
+    /// Constructs a new range, or `None` if both sides are open.
+    pub fn new(from: Option<u64>, to: Option<u64>) -> Option<Range> {
+        if from.is_none() && to.is_none() {
+            return None;
+        }
+
+        Some(Range { from: from, to: to })
+    }
+
+    /// Appends the `name[from]` and `name[to]` parameters for the range to `params`.
+    fn push_params(&self, name: &str, params: &mut Vec<(String, String)>) {
+        if let Some(from) = self.from {
+            params.push((format!("{}[from]", name), from.to_string()));
+        }
+
+        if let Some(to) = self.to {
+            params.push((format!("{}[to]", name), to.to_string()));
+        }
+    }
+}
+
 /// Uploaded track.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Track {
     /// Integer ID.
     pub id: u64,
-    /// Time of which the track was uploaded, as an unparsed string.
-    pub created_at: String,
+    /// Time of which the track was uploaded.
+    #[serde(with = "date")]
+    pub created_at: DateTime<Utc>,
     /// User ID of the uploader.
     pub user_id: u64,
     /// Small representation of the uploaders user.
@@ -74,18 +114,21 @@ pub struct Track {
     /// API resource URL.
     pub uri: String,
     /// Sharing status.
-    pub sharing: String,
+    #[serde(default)]
+    pub sharing: Option<Sharing>,
     /// Who can embed this track.
-    pub embeddable_by: String,
+    #[serde(default)]
+    pub embeddable_by: Option<EmbeddableBy>,
     /// External purchase link.
     pub purchase_url: Option<String>,
     /// URL to a JPEG image.
-    pub artwork_url: Option<String>,
+    pub artwork_url: Option<ArtworkUrl>,
     /// HTML description.
     pub description: Option<String>,
     /// Representation of a labels user.
     pub label: Option<serde_json::Value>,
     /// Duration in milliseconds.
+    #[serde(default)]
     pub duration: u64,
     /// Genre.
     pub genre: Option<String>,
@@ -104,44 +147,58 @@ pub struct Track {
     /// Year of the release.
     pub release_year: Option<u64>,
     /// If the track is available for stream via the API.
+    #[serde(default)]
     pub streamable: bool,
     /// If the track is available for download.
+    #[serde(default)]
     pub downloadable: bool,
     /// Purchase title.
     pub purchase_title: Option<String>,
     /// Encoding state.
-    pub state: String,
+    #[serde(default)]
+    pub state: Option<State>,
     /// Creative common license.
-    pub license: String,
+    #[serde(default)]
+    pub license: Option<License>,
     /// Track type.
-    pub track_type: Option<String>,
+    pub track_type: Option<TrackType>,
     /// URL to waveform PNG image.
+    #[serde(default)]
     pub waveform_url: String,
     /// URL to original file.
     pub download_url: Option<String>,
     /// URL to 128kbps mp3 stream.
     pub stream_url: Option<String>,
+    /// Available encodings for streaming.
+    pub media: Option<Media>,
     /// External video link.
     pub video_url: Option<String>,
     /// Beats per minute.
     pub bpm: Option<u64>,
     /// Commentable.
+    #[serde(default)]
     pub commentable: bool,
     /// ISRC.
     pub isrc: Option<String>,
     /// Key.
     pub key_signature: Option<String>,
     /// Number of comments.
+    #[serde(default)]
     pub comment_count: u64,
     /// Number of downloads.
+    #[serde(default)]
     pub download_count: u64,
     /// Number of playbacks.
+    #[serde(default)]
     pub playback_count: u64,
     /// Number of times favorited.
+    #[serde(default)]
     pub favoritings_count: u64,
     /// Original upload format.
-    pub original_format: String,
+    #[serde(default)]
+    pub original_format: Option<OriginalFormat>,
     /// Original upload size.
+    #[serde(default)]
     pub original_content_size: u64,
     /// Application the track was uploaded with.
     pub created_with: Option<App>,
@@ -159,12 +216,15 @@ pub struct TrackRequestBuilder<'a> {
     query: Option<String>,
     tags: Option<String>,
     filter: Option<Filter>,
-    license: Option<String>,
+    license: Option<License>,
     ids: Option<Vec<usize>>,
-    duration: Option<(usize, usize)>,
-    bpm: Option<(usize, usize)>,
+    duration: Option<Range>,
+    bpm: Option<Range>,
     genres: Option<String>,
-    types: Option<String>,
+    types: Option<Vec<TrackType>>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    linked_partitioning: bool,
 }
 
 #[derive(Debug)]
@@ -184,26 +244,11 @@ impl<'a> SingleTrackRequestBuilder<'a> {
 
     /// Sends the request and return the tracks.
     pub fn get(&mut self) -> Box<Future<Item = Track, Error = Error>> {
-        let no_params: Option<&[(&str, &str)]> = None;
-        let track = self
-            .client
-            .get(&format!("/tracks/{}", self.id), no_params)
-            .map_err(|error| Error::HttpError(error))
-            .and_then(|response| {
-                response
-                    .body()
-                    .concat2()
-                    .map_err(|error| Error::HttpError(error))
-            })
-            .and_then(move |body| {
-                serde_json::from_slice(&body).map_err(|error| Error::JsonError(error))
-            });
-
-        Box::new(track)
+        self.client.get_json(self.request_url().as_str())
     }
 
     pub fn request_url(&self) -> Url {
-        let url = Url::parse(&format!("https://{}/tracks/{}", super::API_HOST, self.id)).unwrap();
+        let url = self.client.api_url(&format!("/tracks/{}", self.id));
 
         url
     }
@@ -223,11 +268,14 @@ impl<'a> TrackRequestBuilder<'a> {
             bpm: None,
             genres: None,
             types: None,
+            limit: None,
+            offset: None,
+            linked_partitioning: false,
         }
     }
 
     /// Sets the search query filter, which will only return tracks with a matching query.
-    pub fn query<S>(&'a mut self, query: Option<S>) -> &mut TrackRequestBuilder
+    pub fn query<S>(&mut self, query: Option<S>) -> &mut TrackRequestBuilder<'a>
     where
         S: AsRef<str>,
     {
@@ -236,7 +284,7 @@ impl<'a> TrackRequestBuilder<'a> {
     }
 
     /// Sets the tags filter, which will only return tracks with a matching tag.
-    pub fn tags<I, T>(&'a mut self, tags: Option<I>) -> &mut TrackRequestBuilder
+    pub fn tags<I, T>(&mut self, tags: Option<I>) -> &mut TrackRequestBuilder<'a>
     where
         I: AsRef<[T]>,
         T: AsRef<str>,
@@ -248,7 +296,7 @@ impl<'a> TrackRequestBuilder<'a> {
         self
     }
 
-    pub fn genres<I, T>(&'a mut self, genres: Option<I>) -> &mut TrackRequestBuilder
+    pub fn genres<I, T>(&mut self, genres: Option<I>) -> &mut TrackRequestBuilder<'a>
     where
         I: AsRef<[T]>,
         T: AsRef<str>,
@@ -261,23 +309,66 @@ impl<'a> TrackRequestBuilder<'a> {
     }
 
     /// Sets whether to filter private or public tracks.
-    pub fn filter(&'a mut self, filter: Option<Filter>) -> &mut TrackRequestBuilder {
+    pub fn filter(&mut self, filter: Option<Filter>) -> &mut TrackRequestBuilder<'a> {
         self.filter = filter;
         self
     }
 
     /// Sets the license filter.
-    pub fn license<S: AsRef<str>>(&'a mut self, license: Option<S>) -> &mut TrackRequestBuilder {
-        self.license = license.map(|s| s.as_ref().to_owned());
+    pub fn license(&mut self, license: Option<License>) -> &mut TrackRequestBuilder<'a> {
+        self.license = license;
+        self
+    }
+
+    /// Sets the track types filter, which will only return tracks of one of the given types.
+    pub fn types<I>(&mut self, types: Option<I>) -> &mut TrackRequestBuilder<'a>
+    where
+        I: AsRef<[TrackType]>,
+    {
+        self.types = types.map(|types| types.as_ref().to_vec());
+        self
+    }
+
+    /// Sets the duration filter in milliseconds. Either side can be left open with `None`.
+    pub fn duration(&mut self, from: Option<u64>, to: Option<u64>) -> &mut TrackRequestBuilder<'a> {
+        self.duration = Range::new(from, to);
+        self
+    }
+
+    /// Sets the beats per minute filter. Either side can be left open with `None`.
+    pub fn bpm(&mut self, from: Option<u64>, to: Option<u64>) -> &mut TrackRequestBuilder<'a> {
+        self.bpm = Range::new(from, to);
         self
     }
 
     /// Sets a list of track ids to look up.
-    pub fn ids(&'a mut self, ids: Option<Vec<usize>>) -> &mut TrackRequestBuilder {
+    pub fn ids(&mut self, ids: Option<Vec<usize>>) -> &mut TrackRequestBuilder<'a> {
         self.ids = ids;
         self
     }
 
+    /// Sets the maximum number of tracks per page, up to 200.
+    pub fn limit(&mut self, limit: Option<usize>) -> &mut TrackRequestBuilder<'a> {
+        self.limit = limit.map(|limit| cmp::min(limit, MAX_PAGE_SIZE));
+        self
+    }
+
+    /// Sets the number of tracks to skip before the first returned track.
+    pub fn offset(&mut self, offset: Option<usize>) -> &mut TrackRequestBuilder<'a> {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets whether the response should be a page with a `next_href` cursor rather than a plain
+    /// list.
+    pub fn linked_partitioning(
+        &mut self,
+        linked_partitioning: bool,
+    ) -> &mut TrackRequestBuilder<'a> {
+        self.linked_partitioning = linked_partitioning;
+        self
+    }
+
     /// Returns a builder for a single track.
     pub fn id(&'a mut self, id: usize) -> SingleTrackRequestBuilder {
         SingleTrackRequestBuilder {
@@ -288,86 +379,297 @@ impl<'a> TrackRequestBuilder<'a> {
 
     /// Performs the request and returns a list of tracks if there are any results, None otherwise,
     /// or an error if one occurred.
+    ///
+    /// Tracks that fail to deserialize are skipped and logged, but the request fails if none of
+    /// them deserialize.
     pub fn get(&mut self) -> Box<Future<Item = Option<Vec<Track>>, Error = Error>> {
         use serde_json::Value;
 
-        let track_list = self
-            .client
-            .get("/tracks", Some(self.request_params()))
-            .map_err(|error| Error::HttpError(error))
-            .and_then(|response| {
-                response
-                    .body()
-                    .concat2()
-                    .map_err(|error| Error::HttpError(error))
-            })
-            .and_then(move |body| {
-                serde_json::from_slice(&body).map_err(|error| Error::JsonError(error))
-            })
-            .and_then(|track_list: Value| {
-                if let Some(track_list) = track_list.as_array() {
-                    if track_list.is_empty() {
-                        return future::ok(None);
-                    } else {
-                        let tracks: Vec<Track> = track_list
-                            .iter()
-                            .map(|t| serde_json::from_value::<Track>(t.clone()).unwrap())
-                            .collect();
-
-                        return future::ok(Some(tracks));
+        let track_list =
+            self.client
+                .get_json(self.request_url().as_str())
+                .and_then(|track_list: Value| {
+                    // With linked partitioning the list is wrapped in a page.
+                    let track_list = match track_list {
+                        Value::Object(mut page) => page.remove("collection").unwrap_or(Value::Null),
+                        track_list => track_list,
+                    };
+
+                    if let Value::Array(track_list) = track_list {
+                        if track_list.is_empty() {
+                            return future::ok(None);
+                        } else {
+                            return future::result(page::deserialize_items(track_list).map(Some));
+                        }
                     }
-                }
 
-                return future::err(Error::ApiError(
-                    "expected response to be an array".to_owned(),
-                ));
-            });
+                    return future::err(Error::ApiError(
+                        "expected response to be an array".to_owned(),
+                    ));
+                });
 
         Box::new(track_list)
     }
 
-    fn request_params(&self) -> Vec<(&str, String)> {
+    /// Returns a stream of every track matching the request, following the pagination cursors
+    /// until the results are exhausted or `max_items` tracks have been returned.
+    ///
+    /// Each page holds `limit` tracks, or 50 if no limit is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use soundcloud::Client;
+    ///
+    /// let client = Client::new(env!("SOUNDCLOUD_CLIENT_ID"));
+    /// let tracks = client.tracks().query(Some("noisia")).limit(Some(200)).paginate(Some(1000));
+    ///
+    /// assert!(tracks.collect().wait().unwrap().len() > 200);
+    /// ```
+    pub fn paginate(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = Track, Error = Error> + 'a> {
+        let mut url = self.request_url();
+
+        {
+            let mut query_pairs = url.query_pairs_mut();
+
+            if self.limit.is_none() {
+                query_pairs.append_pair("limit", &DEFAULT_PAGE_SIZE.to_string());
+            }
+
+            if !self.linked_partitioning {
+                query_pairs.append_pair("linked_partitioning", "1");
+            }
+        }
+
+        page::paginate(self.client, url, max_items)
+    }
+
+    /// Returns the url of the request, without the `client_id` parameter.
+    pub fn request_url(&self) -> Url {
+        let mut url = self.client.api_url("/tracks");
+        let params = self.request_params();
+
+        if !params.is_empty() {
+            url.query_pairs_mut().extend_pairs(params);
+        }
+
+        url
+    }
+
+    fn request_params(&self) -> Vec<(String, String)> {
         let mut result = vec![];
 
         if let Some(ref query) = self.query {
-            result.push(("q", query.clone()));
+            result.push(("q".to_owned(), query.clone()));
         }
 
         if let Some(ref tags) = self.tags {
-            result.push(("tags", tags.clone()));
+            result.push(("tags".to_owned(), tags.clone()));
         }
 
         if let Some(ref filter) = self.filter {
-            result.push(("filter", filter.to_str().to_owned()));
+            result.push(("filter".to_owned(), filter.to_str().to_owned()));
+        }
+
+        if let Some(ref license) = self.license {
+            result.push(("license".to_owned(), license.to_str().to_owned()));
         }
 
         if let Some(ref ids) = self.ids {
             let ids_as_strings: Vec<String> = ids.iter().map(|id| format!("{}", id)).collect();
-            result.push(("ids", ids_as_strings.join(",")));
+            result.push(("ids".to_owned(), ids_as_strings.join(",")));
         }
 
-        if let Some(ref _duration) = self.duration {
-            unimplemented!();
+        if let Some(ref duration) = self.duration {
+            duration.push_params("duration", &mut result);
         }
 
-        if let Some(ref _bpm) = self.bpm {
-            unimplemented!();
+        if let Some(ref bpm) = self.bpm {
+            bpm.push_params("bpm", &mut result);
         }
 
         if let Some(ref genres) = self.genres {
-            result.push(("genres", genres.clone()));
+            result.push(("genres".to_owned(), genres.clone()));
         }
 
         if let Some(ref types) = self.types {
-            result.push(("types", types.clone()));
+            let types: Vec<&str> = types.iter().map(TrackType::to_str).collect();
+            result.push(("types".to_owned(), types.join(",")));
+        }
+
+        if let Some(limit) = self.limit {
+            result.push(("limit".to_owned(), limit.to_string()));
+        }
+
+        if let Some(offset) = self.offset {
+            result.push(("offset".to_owned(), offset.to_string()));
+        }
+
+        if self.linked_partitioning {
+            result.push(("linked_partitioning".to_owned(), "1".to_owned()));
         }
 
         result
     }
 }
 
+impl Track {
+    /// Returns the release date, if the day, month and year of the release are all set.
+    pub fn release_date(&self) -> Option<NaiveDate> {
+        date::from_parts(self.release_year, self.release_month, self.release_day)
+    }
+
+    /// Returns the available encodings for streaming.
+    pub fn transcodings(&self) -> &[Transcoding] {
+        match self.media {
+            Some(ref media) => &media.transcodings,
+            None => &[],
+        }
+    }
+
+    /// Returns the full-length transcoding with the most preferred protocol in `protocols` and,
+    /// among those, the most preferred format in `formats`.
+    pub fn transcoding(
+        &self,
+        protocols: &[Protocol],
+        formats: &[AudioFormat],
+    ) -> Option<&Transcoding> {
+        transcoding::preferred(self.transcodings(), protocols, formats)
+    }
+}
+
 impl PartialEq for Track {
     fn eq(&self, other: &Track) -> bool {
         other.id == self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+    use fixtures;
+    use tokio_core;
+
+    fn client(handle: &tokio_core::reactor::Handle) -> Client {
+        Client::new("abc", handle)
+    }
+
+    #[test]
+    fn test_dates() {
+        let mut track = fixtures::track();
+
+        assert_eq!(track.created_at.year(), 2011);
+        assert_eq!(track.created_at.minute(), 37);
+        assert_eq!(track.release_date(), NaiveDate::from_ymd_opt(2011, 4, 6));
+
+        track.release_day = None;
+        assert_eq!(track.release_date(), None);
+
+        let json = serde_json::to_value(&track).unwrap();
+        assert_eq!(json["created_at"], "2011/04/06 15:37:43 +0000");
+    }
+
+    #[test]
+    fn test_duration_range_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client
+            .tracks()
+            .duration(Some(60000), Some(120000))
+            .request_url();
+
+        assert_eq!(
+            url.query(),
+            Some("duration%5Bfrom%5D=60000&duration%5Bto%5D=120000")
+        );
+    }
+
+    #[test]
+    fn test_open_ended_range_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client
+            .tracks()
+            .bpm(Some(120), None)
+            .duration(None, Some(300000))
+            .request_url();
+
+        assert_eq!(
+            url.query(),
+            Some("duration%5Bto%5D=300000&bpm%5Bfrom%5D=120")
+        );
+    }
+
+    #[test]
+    fn test_pagination_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client
+            .tracks()
+            .limit(Some(500))
+            .offset(Some(100))
+            .linked_partitioning(true)
+            .request_url();
+
+        assert_eq!(
+            url.query(),
+            Some("limit=200&offset=100&linked_partitioning=1")
+        );
+    }
+
+    #[test]
+    fn test_license_and_types_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client
+            .tracks()
+            .license(Some(License::CcByNc))
+            .types(Some(&[TrackType::Remix, TrackType::InProgress]))
+            .request_url();
+
+        assert_eq!(
+            url.query(),
+            Some("license=cc-by-nc&types=remix%2Cin+progress")
+        );
+    }
+
+    #[test]
+    fn test_metadata() {
+        let track = fixtures::track();
+
+        assert_eq!(track.sharing, Some(Sharing::Public));
+        assert_eq!(track.embeddable_by, Some(EmbeddableBy::All));
+        assert_eq!(track.state, Some(State::Finished));
+        assert_eq!(track.license, Some(License::AllRightsReserved));
+        assert_eq!(
+            track.track_type,
+            Some(TrackType::Unknown("field".to_owned()))
+        );
+        assert_eq!(track.original_format, Some(OriginalFormat::M4a));
+    }
+
+    #[test]
+    fn test_mini_track() {
+        let track: Track = serde_json::from_value(fixtures::mini_track()).unwrap();
+
+        assert_eq!(track.sharing, None);
+        assert_eq!(track.state, None);
+        assert_eq!(track.original_format, None);
+        assert_eq!(track.playback_count, 0);
+        assert!(!track.streamable);
+        assert!(track.user.avatar_url.is_none());
+    }
+
+    #[test]
+    fn test_empty_range_is_omitted() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client.tracks().bpm(None, None).request_url();
+
+        assert_eq!(url.query(), None);
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Protocol that a transcoding is delivered with.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// HTTP Live Streaming, a playlist of short segments.
+    Hls,
+    /// A single file that can be downloaded progressively.
+    Progressive,
+    /// Any protocol this library doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Audio codec of a transcoding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioFormat {
+    /// MPEG-1 Audio Layer III.
+    Mp3,
+    /// Opus, usually in an Ogg container.
+    Opus,
+    /// Any other codec.
+    Unknown,
+}
+
+/// Protocol and MIME type of a transcoding.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TranscodingFormat {
+    /// Delivery protocol.
+    pub protocol: Protocol,
+    /// MIME type of the audio, e.g. `audio/mpeg`.
+    pub mime_type: String,
+}
+
+/// Encoding of a track that can be streamed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Transcoding {
+    /// URL that returns the location of the audio.
+    pub url: String,
+    /// Name of the encoding preset, e.g. `mp3_0_0`.
+    pub preset: String,
+    /// Duration in milliseconds.
+    pub duration: u64,
+    /// If the transcoding is only a preview of the track.
+    #[serde(default)]
+    pub snipped: bool,
+    /// Protocol and MIME type.
+    pub format: TranscodingFormat,
+    /// Quality, e.g. `sq` or `hq`.
+    pub quality: Option<String>,
+}
+
+impl Transcoding {
+    /// Returns the protocol the transcoding is delivered with.
+    pub fn protocol(&self) -> Protocol {
+        self.format.protocol
+    }
+
+    /// Returns the audio codec, as determined by the MIME type.
+    pub fn audio_format(&self) -> AudioFormat {
+        let mime_type = &self.format.mime_type;
+
+        if mime_type.starts_with("audio/mpeg") {
+            AudioFormat::Mp3
+        } else if mime_type.contains("opus") {
+            AudioFormat::Opus
+        } else {
+            AudioFormat::Unknown
+        }
+    }
+}
+
+/// Media information of a track.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Media {
+    /// Available encodings of the track.
+    #[serde(default)]
+    pub transcodings: Vec<Transcoding>,
+}
+
+/// Response to a request for a transcoding url.
+#[derive(Deserialize, Debug)]
+pub struct MediaLocation {
+    /// URL of the audio or HLS playlist.
+    pub url: String,
+}
+
+/// Returns the first full-length transcoding in `transcodings` with the most preferred protocol
+/// in `protocols` and, among those, the most preferred format in `formats`.
+pub fn preferred<'a>(
+    transcodings: &'a [Transcoding],
+    protocols: &[Protocol],
+    formats: &[AudioFormat],
+) -> Option<&'a Transcoding> {
+    for protocol in protocols {
+        for format in formats {
+            let transcoding = transcodings.iter().find(|transcoding| {
+                !transcoding.snipped
+                    && transcoding.protocol() == *protocol
+                    && transcoding.audio_format() == *format
+            });
+
+            if transcoding.is_some() {
+                return transcoding;
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn transcodings() -> Vec<Transcoding> {
+        serde_json::from_str(
+            r#"[
+            {"url": "https://api-v2.soundcloud.com/media/1/hls-mp3", "preset": "mp3_0_0",
+             "duration": 1000, "snipped": false, "quality": "sq",
+             "format": {"protocol": "hls", "mime_type": "audio/mpeg"}},
+            {"url": "https://api-v2.soundcloud.com/media/1/progressive", "preset": "mp3_0_0",
+             "duration": 1000, "snipped": false, "quality": "sq",
+             "format": {"protocol": "progressive", "mime_type": "audio/mpeg"}},
+            {"url": "https://api-v2.soundcloud.com/media/1/hls-opus", "preset": "opus_0_0",
+             "duration": 1000, "snipped": false, "quality": "sq",
+             "format": {"protocol": "hls", "mime_type": "audio/ogg; codecs=\"opus\""}},
+            {"url": "https://api-v2.soundcloud.com/media/1/dash", "preset": "aac_0_0",
+             "duration": 1000, "quality": "hq",
+             "format": {"protocol": "dash", "mime_type": "audio/mp4"}}
+        ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_transcodings() {
+        let transcodings = transcodings();
+
+        assert_eq!(transcodings[0].protocol(), Protocol::Hls);
+        assert_eq!(transcodings[0].audio_format(), AudioFormat::Mp3);
+        assert_eq!(transcodings[2].audio_format(), AudioFormat::Opus);
+        assert_eq!(transcodings[3].protocol(), Protocol::Unknown);
+        assert!(!transcodings[3].snipped);
+    }
+
+    #[test]
+    fn test_preferred_transcoding() {
+        let transcodings = transcodings();
+
+        let transcoding = preferred(
+            &transcodings,
+            &[Protocol::Progressive, Protocol::Hls],
+            &[AudioFormat::Opus, AudioFormat::Mp3],
+        );
+        assert_eq!(
+            transcoding.unwrap().url,
+            "https://api-v2.soundcloud.com/media/1/progressive"
+        );
+
+        let transcoding = preferred(
+            &transcodings,
+            &[Protocol::Hls],
+            &[AudioFormat::Opus, AudioFormat::Mp3],
+        );
+        assert_eq!(
+            transcoding.unwrap().url,
+            "https://api-v2.soundcloud.com/media/1/hls-opus"
+        );
+
+        assert!(preferred(
+            &transcodings,
+            &[Protocol::Progressive],
+            &[AudioFormat::Opus]
+        )
+        .is_none());
+    }
+}
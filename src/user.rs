@@ -0,0 +1,379 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp;
+
+use chrono::{DateTime, Utc};
+use futures::{Future, Stream};
+use serde::de::DeserializeOwned;
+use serde_json;
+use url::Url;
+
+use artwork::ArtworkUrl;
+use client::{Client, Comment};
+use date;
+use error::Error;
+use page::{self, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use playlist::Playlist;
+use track::Track;
+
+/// Registered user.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct User {
+    /// Integer ID.
+    pub id: usize,
+    /// Permalink of the resource.
+    pub permalink: String,
+    /// Username.
+    pub username: String,
+    /// API resource URL.
+    pub uri: String,
+    /// URL to the SoundCloud.com page.
+    pub permalink_url: String,
+    /// URL to a JPEG image.
+    pub avatar_url: Option<ArtworkUrl>,
+    /// Country.
+    pub country: Option<String>,
+    /// First and last name.
+    pub full_name: Option<String>,
+    /// City.
+    pub city: Option<String>,
+    /// Description, written by the user.
+    pub description: Option<String>,
+    /// Discogs name.
+    #[serde(rename = "discogs-name")]
+    pub discogs_name: Option<String>, // discogs-name
+    /// MySpace name.
+    #[serde(rename = "myspace-name")]
+    pub myspace_name: Option<String>, // myspace-name
+    /// URL to a website.
+    pub website: Option<String>,
+    /// Custom title for the website.
+    #[serde(rename = "website-title")]
+    pub website_title: Option<String>, // website-title
+    /// Online status.
+    pub online: Option<bool>,
+    /// Number of public tracks.
+    pub track_count: Option<usize>,
+    /// Number of public playlists.
+    pub playlist_count: Option<usize>,
+    /// Number of followers.
+    pub followers_count: Option<usize>,
+    /// Number of followed users.
+    pub followings_count: Option<usize>,
+    /// Number of favorited public tracks.
+    pub public_favorites_count: Option<usize>,
+    // pub avatar_data …
+}
+
+/// Link to a user's profile on an external service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebProfile {
+    /// Integer ID.
+    pub id: usize,
+    /// Name of the service, e.g. `twitter` or `personal`.
+    pub service: String,
+    /// Custom title for the link.
+    pub title: Option<String>,
+    /// URL to the external profile.
+    pub url: String,
+    /// Username on the external service.
+    pub username: Option<String>,
+    /// Time of creation.
+    #[serde(default, with = "date::option")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct UserRequestBuilder<'a> {
+    client: &'a Client,
+    query: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SingleUserRequestBuilder<'a> {
+    client: &'a Client,
+    pub id: usize,
+}
+
+impl<'a> SingleUserRequestBuilder<'a> {
+    /// Constructs a new user request.
+    pub fn new(client: &'a Client, id: usize) -> SingleUserRequestBuilder<'a> {
+        SingleUserRequestBuilder {
+            client: client,
+            id: id,
+        }
+    }
+
+    /// Sends the request and returns the user.
+    pub fn get(&mut self) -> Box<Future<Item = User, Error = Error>> {
+        self.client.get_json(self.request_url().as_str())
+    }
+
+    /// Returns a stream of the tracks uploaded by the user.
+    pub fn tracks(&self, max_items: Option<u64>) -> Box<Stream<Item = Track, Error = Error> + 'a> {
+        self.paginate("tracks", max_items)
+    }
+
+    /// Returns a stream of the playlists created by the user.
+    pub fn playlists(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = Playlist, Error = Error> + 'a> {
+        self.paginate("playlists", max_items)
+    }
+
+    /// Returns a stream of the users followed by the user.
+    pub fn followings(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = User, Error = Error> + 'a> {
+        self.paginate("followings", max_items)
+    }
+
+    /// Returns a stream of the users following the user.
+    pub fn followers(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = User, Error = Error> + 'a> {
+        self.paginate("followers", max_items)
+    }
+
+    /// Returns a stream of the tracks favorited by the user.
+    pub fn favorites(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = Track, Error = Error> + 'a> {
+        self.paginate("favorites", max_items)
+    }
+
+    /// Returns a stream of the comments posted by the user.
+    pub fn comments(
+        &self,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = Comment, Error = Error> + 'a> {
+        self.paginate("comments", max_items)
+    }
+
+    /// Returns the user's links to external profiles.
+    ///
+    /// The API returns these as a single list rather than in pages.
+    pub fn web_profiles(&self) -> Box<Future<Item = Vec<WebProfile>, Error = Error>> {
+        let url = self
+            .client
+            .api_url(&format!("/users/{}/web-profiles", self.id));
+
+        self.client.get_json(url.as_str())
+    }
+
+    pub fn request_url(&self) -> Url {
+        let url = self.client.api_url(&format!("/users/{}", self.id));
+
+        url
+    }
+
+    /// Returns a stream of every item in the user's sub-resource collection.
+    fn paginate<T>(
+        &self,
+        resource: &str,
+        max_items: Option<u64>,
+    ) -> Box<Stream<Item = T, Error = Error> + 'a>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let mut url = self
+            .client
+            .api_url(&format!("/users/{}/{}", self.id, resource));
+
+        url.query_pairs_mut()
+            .append_pair("limit", &DEFAULT_PAGE_SIZE.to_string())
+            .append_pair("linked_partitioning", "1");
+
+        page::paginate(self.client, url, max_items)
+    }
+}
+
+impl<'a> UserRequestBuilder<'a> {
+    /// Creates a new user request builder, with no set parameters.
+    pub fn new(client: &'a Client) -> UserRequestBuilder<'a> {
+        UserRequestBuilder {
+            client: client,
+            query: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Sets the search query filter, which will only return users with a matching query.
+    pub fn query<S>(&mut self, query: Option<S>) -> &mut UserRequestBuilder<'a>
+    where
+        S: AsRef<str>,
+    {
+        self.query = query.map(|s| s.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the maximum number of users per page, up to 200.
+    pub fn limit(&mut self, limit: Option<usize>) -> &mut UserRequestBuilder<'a> {
+        self.limit = limit.map(|limit| cmp::min(limit, MAX_PAGE_SIZE));
+        self
+    }
+
+    /// Sets the number of users to skip before the first returned user.
+    pub fn offset(&mut self, offset: Option<usize>) -> &mut UserRequestBuilder<'a> {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns a builder for a single user.
+    pub fn id(&self, id: usize) -> SingleUserRequestBuilder<'a> {
+        SingleUserRequestBuilder::new(self.client, id)
+    }
+
+    /// Performs the request and returns a list of users if there are any results, None otherwise,
+    /// or an error if one occurred.
+    ///
+    /// Users that fail to deserialize are skipped and logged, but the request fails if none of
+    /// them deserialize.
+    pub fn get(&mut self) -> Box<Future<Item = Option<Vec<User>>, Error = Error>> {
+        let user_list = self.client.get_json(self.request_url().as_str()).and_then(
+            |user_list: Vec<serde_json::Value>| {
+                if user_list.is_empty() {
+                    Ok(None)
+                } else {
+                    page::deserialize_items(user_list).map(Some)
+                }
+            },
+        );
+
+        Box::new(user_list)
+    }
+
+    /// Returns a stream of every user matching the request, following the pagination cursors
+    /// until the results are exhausted or `max_items` users have been returned.
+    pub fn paginate(&self, max_items: Option<u64>) -> Box<Stream<Item = User, Error = Error> + 'a> {
+        let mut url = self.request_url();
+
+        if self.limit.is_none() {
+            url.query_pairs_mut()
+                .append_pair("limit", &DEFAULT_PAGE_SIZE.to_string());
+        }
+
+        url.query_pairs_mut()
+            .append_pair("linked_partitioning", "1");
+
+        page::paginate(self.client, url, max_items)
+    }
+
+    /// Returns the url of the request, without the `client_id` parameter.
+    pub fn request_url(&self) -> Url {
+        let mut url = self.client.api_url("/users");
+        let params = self.request_params();
+
+        if !params.is_empty() {
+            url.query_pairs_mut().extend_pairs(params);
+        }
+
+        url
+    }
+
+    fn request_params(&self) -> Vec<(&str, String)> {
+        let mut result = vec![];
+
+        if let Some(ref query) = self.query {
+            result.push(("q", query.clone()));
+        }
+
+        if let Some(limit) = self.limit {
+            result.push(("limit", limit.to_string()));
+        }
+
+        if let Some(offset) = self.offset {
+            result.push(("offset", offset.to_string()));
+        }
+
+        result
+    }
+}
+
+impl PartialEq for User {
+    fn eq(&self, other: &User) -> bool {
+        other.id == self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_core;
+
+    fn client(handle: &tokio_core::reactor::Handle) -> Client {
+        Client::new("abc", handle)
+    }
+
+    #[test]
+    fn test_deserialize_web_profiles() {
+        let profiles: Vec<WebProfile> = serde_json::from_str(
+            r#"[{"id": 1, "service": "twitter", "title": null, "username": "alexstevenson",
+                 "url": "https://twitter.com/alexstevenson",
+                 "created_at": "2011/04/06 15:37:43 +0000"},
+                {"id": 2, "service": "personal", "title": "Home",
+                 "url": "https://example.com", "created_at": null}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            profiles[0].created_at.map(|date| date.to_rfc3339()),
+            Some("2011-04-06T15:37:43+00:00".to_owned())
+        );
+        assert_eq!(profiles[1].username, None);
+        assert_eq!(profiles[1].created_at, None);
+    }
+
+    #[test]
+    fn test_search_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client
+            .users()
+            .query(Some("noisia"))
+            .limit(Some(500))
+            .offset(Some(50))
+            .request_url();
+
+        assert_eq!(url.path(), "/users");
+        assert_eq!(url.query(), Some("q=noisia&limit=200&offset=50"));
+    }
+
+    #[test]
+    fn test_empty_params() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+        let url = client.users().query(None::<&str>).request_url();
+
+        assert_eq!(url.query(), None);
+    }
+
+    #[test]
+    fn test_single_user_url() {
+        let core = tokio_core::reactor::Core::new().unwrap();
+        let client = client(&core.handle());
+
+        assert_eq!(
+            client.user(3207).request_url().as_str(),
+            "https://api.soundcloud.com/users/3207"
+        );
+        assert_eq!(
+            client.users().id(3207).request_url().as_str(),
+            "https://api.soundcloud.com/users/3207"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright (c) 2016, Mikkel Kroman <mk@uplink.io>
+// All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp;
+
+use png::{self, HasParameters};
+
+use error::Result;
+
+/// Host that serves waveforms as JSON.
+const JSON_HOST: &'static str = "wis.sndcdn.com";
+
+/// Heights of the waveform of a track, from start to end.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Waveform {
+    /// Number of samples.
+    pub width: u32,
+    /// Maximum height of a sample.
+    pub height: u32,
+    /// Height of every sample, between 0 and `height`.
+    pub samples: Vec<u32>,
+}
+
+impl Waveform {
+    /// Extracts a waveform from a waveform PNG image, with a sample for every column of pixels.
+    ///
+    /// The height of a sample is the number of pixels in the column that belong to the waveform,
+    /// which are the transparent pixels of images with an alpha channel and the dark pixels of
+    /// images without one. Palette and 16-bit images are converted to 8-bit samples first.
+    pub fn from_png(image: &[u8]) -> Result<Waveform> {
+        let mut decoder = png::Decoder::new(image);
+        decoder.set(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+        let (info, mut reader) = decoder.read_info()?;
+
+        if info.bit_depth != png::BitDepth::Eight {
+            return Err(png::DecodingError::Other("unsupported bit depth".into()).into());
+        }
+
+        let mut pixels = vec![0; info.buffer_size()];
+        reader.next_frame(&mut pixels)?;
+
+        let channels = info.color_type.samples();
+        let has_alpha = match info.color_type {
+            png::ColorType::GrayscaleAlpha | png::ColorType::RGBA => true,
+            _ => false,
+        };
+
+        let mut samples = vec![0; info.width as usize];
+
+        for row in pixels.chunks(info.line_size) {
+            for (x, pixel) in row.chunks(channels).take(info.width as usize).enumerate() {
+                let is_wave = if has_alpha {
+                    pixel[channels - 1] < 128
+                } else {
+                    let sum: u32 = pixel.iter().map(|&value| value as u32).sum();
+                    sum / (channels as u32) < 128
+                };
+
+                if is_wave {
+                    samples[x] += 1;
+                }
+            }
+        }
+
+        Ok(Waveform {
+            width: info.width,
+            height: info.height,
+            samples: samples,
+        })
+    }
+
+    /// Returns `width` samples, each the highest of the samples it covers, so that peaks are
+    /// kept when the waveform is shrunk. Samples are repeated when it is stretched.
+    pub fn resample(&self, width: usize) -> Vec<u32> {
+        let len = self.samples.len();
+
+        if len == 0 {
+            return vec![0; width];
+        }
+
+        (0..width)
+            .map(|index| {
+                let start = index * len / width;
+                let end = cmp::max((index + 1) * len / width, start + 1);
+
+                self.samples[start..cmp::min(end, len)]
+                    .iter()
+                    .cloned()
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    /// Returns the samples scaled to between 0 and 1.
+    pub fn normalized(&self) -> Vec<f32> {
+        if self.height == 0 {
+            return vec![0.0; self.samples.len()];
+        }
+
+        self.samples
+            .iter()
+            .map(|&sample| sample as f32 / self.height as f32)
+            .collect()
+    }
+}
+
+/// Returns the url of the JSON representation of the waveform at `waveform_url`, or `None` if
+/// `waveform_url` already points at JSON.
+pub fn json_url(waveform_url: &str) -> Option<String> {
+    if waveform_url.ends_with(".json") {
+        return None;
+    }
+
+    let path_start = waveform_url.find("://").map_or(0, |index| index + 3);
+    let path_start = waveform_url[path_start..]
+        .find('/')
+        .map_or(waveform_url.len(), |index| path_start + index);
+    let path = &waveform_url[path_start..];
+    let stem = path.rfind('.').map_or(path, |index| &path[..index]);
+
+    Some(format!("https://{}{}.json", JSON_HOST, stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a `width`x`height` PNG image with the given `chunks` before the image data.
+    fn encode(
+        width: u32,
+        height: u32,
+        color_type: png::ColorType,
+        bit_depth: png::BitDepth,
+        chunks: &[([u8; 4], &[u8])],
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let mut image = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut image, width, height);
+            encoder.set(color_type).set(bit_depth);
+            let mut writer = encoder.write_header().unwrap();
+
+            for &(name, data) in chunks.iter() {
+                writer.write_chunk(name, data).unwrap();
+            }
+
+            writer.write_image_data(pixels).unwrap();
+        }
+
+        image
+    }
+
+    #[test]
+    fn test_json_url() {
+        assert_eq!(
+            json_url("https://w1.sndcdn.com/fxguEjG4ax6B_m.png"),
+            Some("https://wis.sndcdn.com/fxguEjG4ax6B_m.json".to_owned())
+        );
+        assert_eq!(
+            json_url("https://wave.sndcdn.com/fxguEjG4ax6B_m.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resample() {
+        let waveform = Waveform {
+            width: 6,
+            height: 10,
+            samples: vec![1, 5, 2, 8, 3, 4],
+        };
+
+        assert_eq!(waveform.resample(3), vec![5, 8, 4]);
+        assert_eq!(waveform.resample(6), waveform.samples);
+        assert_eq!(waveform.resample(12)[..4], [1, 1, 5, 5]);
+        assert_eq!(waveform.resample(1), vec![8]);
+    }
+
+    #[test]
+    fn test_normalized() {
+        let waveform = Waveform {
+            width: 3,
+            height: 4,
+            samples: vec![0, 2, 4],
+        };
+
+        assert_eq!(waveform.normalized(), vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_from_png() {
+        // 3x4 RGBA image with transparent columns of height 0, 2 and 4.
+        let heights = [0, 2, 4];
+        let mut pixels = vec![];
+
+        for y in 0..4 {
+            for &height in heights.iter() {
+                let is_wave = y >= 4 - height;
+                pixels.extend_from_slice(&[239, 239, 239, if is_wave { 0 } else { 255 }]);
+            }
+        }
+
+        let image = encode(
+            3,
+            4,
+            png::ColorType::RGBA,
+            png::BitDepth::Eight,
+            &[],
+            &pixels,
+        );
+        let waveform = Waveform::from_png(&image).unwrap();
+
+        assert_eq!(waveform.width, 3);
+        assert_eq!(waveform.height, 4);
+        assert_eq!(waveform.samples, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn test_from_16_bit_png() {
+        // 3x4 16-bit RGBA image with transparent columns of height 1, 3 and 0.
+        let heights = [1, 3, 0];
+        let mut pixels = vec![];
+
+        for y in 0..4 {
+            for &height in heights.iter() {
+                let alpha = if y >= 4 - height { 0 } else { 255 };
+                pixels.extend_from_slice(&[239, 239, 239, 239, 239, 239, alpha, alpha]);
+            }
+        }
+
+        let image = encode(
+            3,
+            4,
+            png::ColorType::RGBA,
+            png::BitDepth::Sixteen,
+            &[],
+            &pixels,
+        );
+
+        assert_eq!(Waveform::from_png(&image).unwrap().samples, vec![1, 3, 0]);
+    }
+
+    #[test]
+    fn test_from_palette_png() {
+        // 3x4 image with a light and a dark palette entry and dark columns of height 2, 0 and 4.
+        let heights = [2, 0, 4];
+        let mut pixels = vec![];
+
+        for y in 0..4 {
+            for &height in heights.iter() {
+                pixels.push(if y >= 4 - height { 1 } else { 0 });
+            }
+        }
+
+        let palette = [239, 239, 239, 0, 0, 0];
+        let image = encode(
+            3,
+            4,
+            png::ColorType::Indexed,
+            png::BitDepth::Eight,
+            &[(*b"PLTE", &palette)],
+            &pixels,
+        );
+
+        assert_eq!(Waveform::from_png(&image).unwrap().samples, vec![2, 0, 4]);
+    }
+
+    #[test]
+    fn test_from_invalid_png() {
+        assert!(Waveform::from_png(b"not a png").is_err());
+    }
+}